@@ -0,0 +1,127 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::BundlessOptions;
+
+/// banner / footer 模板
+/// - `[name]`: 源文件名 (不含后缀), e.g. `index`
+/// - `[path]`: 相对 src 目录的源文件路径, e.g. `utils/index.ts`
+fn render(template: &str, file: &Path, src_dir: &Path) -> String {
+    let name = file.file_stem().unwrap_or_default().to_string_lossy();
+    let path = file.strip_prefix(src_dir).unwrap_or(file);
+    let path = path.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>();
+
+    // str::replace 被 clippy.toml 禁用
+    let rendered = template.split("[name]").collect::<Vec<_>>().join(&name);
+    rendered.split("[path]").collect::<Vec<_>>().join(&path.join("/"))
+}
+
+/// 产物添加 banner / footer
+///
+/// banner 位于文件最前 (可以是 "use client" 等指令), 有 `#!` 时位于其后, sourcemap 按 banner 行数偏移
+pub(crate) fn add_banner_footer(
+    code: &mut String,
+    map: &mut Option<String>,
+    file: &Path,
+    options: &BundlessOptions,
+) -> Result<()> {
+    let src_dir = options.src_dir();
+
+    if let Some(banner) = &options.banner {
+        let banner = render(banner, file, &src_dir);
+
+        // #! 只能位于第一行
+        let (index, line) = if code.starts_with("#!") {
+            if !code.contains('\n') {
+                code.push('\n');
+            }
+            (code.find('\n').map_or(code.len(), |i| i + 1), 1)
+        } else {
+            (0, 0)
+        };
+        code.insert_str(index, &format!("{banner}\n"));
+
+        if let Some(map) = map {
+            *map = offset_source_map(map, line, banner.split('\n').count())?;
+        }
+    }
+
+    if let Some(footer) = &options.footer {
+        let footer = render(footer, file, &src_dir);
+        if !code.ends_with('\n') {
+            code.push('\n');
+        }
+        code.push_str(&footer);
+    }
+
+    Ok(())
+}
+
+// 产物第 line 行 (从 0 开始) 前插入 lines 行: mappings 第 line 个 ';' 后补 lines 个 ';'
+fn offset_source_map(map: &str, line: usize, lines: usize) -> Result<String> {
+    let mut map: serde_json::Value = serde_json::from_str(map)?;
+
+    if let Some(serde_json::Value::String(mappings)) = map.get_mut("mappings") {
+        let index = match line {
+            0 => Some(0),
+            _ => mappings.match_indices(';').nth(line - 1).map(|(i, _)| i + 1),
+        };
+        // 无对应行: 插入位置之后没有映射, 无需偏移
+        if let Some(index) = index {
+            mappings.insert_str(index, &";".repeat(lines));
+        }
+    }
+
+    Ok(serde_json::to_string(&map)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let src_dir = Path::new("/demo/src");
+        let file = Path::new("/demo/src/utils/index.ts");
+
+        assert_eq!(render("/*! [name] */", file, src_dir), "/*! index */");
+        assert_eq!(render("// [path] ([name])", file, src_dir), "// utils/index.ts (index)");
+        assert_eq!(render("\"use client\";", file, src_dir), "\"use client\";");
+    }
+
+    #[test]
+    fn test_add_banner_footer() {
+        let options = BundlessOptions::default()
+            .cwd("/demo")
+            .banner("/**\n * @license MIT\n */")
+            .footer("// [name]");
+        let mut code = "console.log(1);\n".to_string();
+        let mut map = Some(r#"{"version":3,"sources":["index.ts"],"mappings":"AAAA"}"#.to_string());
+
+        add_banner_footer(&mut code, &mut map, Path::new("/demo/src/index.ts"), &options).unwrap();
+
+        assert_eq!(code, "/**\n * @license MIT\n */\nconsole.log(1);\n// index");
+        let map: serde_json::Value = serde_json::from_str(map.as_deref().unwrap()).unwrap();
+        assert_eq!(map["mappings"], ";;;AAAA");
+    }
+
+    #[test]
+    fn test_banner_after_shebang() {
+        let options = BundlessOptions::default().cwd("/demo").banner("/*! [name] */");
+        let mut code = "#!/usr/bin/env node\nconsole.log(1);\n".to_string();
+        let mut map =
+            Some(r#"{"version":3,"sources":["cli.ts"],"mappings":"AAAA;AACA"}"#.to_string());
+
+        add_banner_footer(&mut code, &mut map, Path::new("/demo/src/cli.ts"), &options).unwrap();
+
+        assert_eq!(code, "#!/usr/bin/env node\n/*! cli */\nconsole.log(1);\n");
+        let map: serde_json::Value = serde_json::from_str(map.as_deref().unwrap()).unwrap();
+        assert_eq!(map["mappings"], "AAAA;;AACA");
+
+        // 只有 #! 一行
+        let mut code = "#!/usr/bin/env node".to_string();
+        add_banner_footer(&mut code, &mut None, Path::new("/demo/src/cli.ts"), &options).unwrap();
+        assert_eq!(code, "#!/usr/bin/env node\n/*! cli */\n");
+    }
+}
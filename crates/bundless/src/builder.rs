@@ -0,0 +1,253 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use swc_core::ecma::ast::{Pass, Program, noop_pass};
+
+use crate::BundlessOptions;
+use crate::hooks::{BundlessHooks, Hooks};
+
+/// 创建 swc pass, 每个文件调用一次
+pub type PassFactory = dyn Fn(&Path, &BundlessOptions) -> Box<dyn Pass> + Send + Sync;
+
+/// before: 先于 swc 内置转换 (ts, jsx, 模块转换), after: swc 内置转换之后
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PassPhase {
+    Before,
+    After,
+}
+
+/// 内置 passes (before), 可通过 [BundlessBuilder::disable_pass] 关闭
+///
+/// - define: import.meta.env.X, process.env.X (env), 用户 define 由 swc optimizer.globals 替换
+/// - alias: `@/utils` -> `./utils`, 不修改后缀
+/// - resolve-extensions: 补全无后缀 / 目录导入 (产物后缀), `./utils` -> `./utils.mjs`, `./components` -> `./components/index.js`
+/// - ts2js: `./a.ts` -> `./a.js`
+/// - cjs-interop: export default -> module.exports = default
+/// - top-level-await: cjs 产物中的 top-level await (报错或包裹为 async IIFE)
+/// - import-meta: cjs 产物中的 import.meta.url, import.meta.dirname, ...
+pub const BEFORE_PASSES: [&str; 7] = [
+    "define",
+    "alias",
+    "resolve-extensions",
+    "ts2js",
+    "cjs-interop",
+    "top-level-await",
+    "import-meta",
+];
+
+/// 内置 passes (after), 可通过 [BundlessBuilder::disable_pass] 关闭
+///
+/// - json: import attributes / createRequire
+/// - extensions: `.js`, `.mjs`, `.cjs` 导入 -> 产物后缀
+/// - shims: __dirname, require (esm), global (browser), ...
+/// - css-modules: .css 导入
+pub const AFTER_PASSES: [&str; 4] = ["json", "extensions", "shims", "css-modules"];
+
+#[derive(Clone)]
+struct CustomPass {
+    name: String,
+    phase: PassPhase,
+    factory: Arc<PassFactory>,
+}
+
+/// 自定义 passes + 关闭的内置 passes
+#[derive(Clone, Default)]
+pub(crate) struct PassRegistry {
+    custom: Vec<CustomPass>,
+    disabled: HashSet<String>,
+}
+
+impl fmt::Debug for PassRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PassRegistry")
+            .field("custom", &self.custom.iter().map(|p| (&p.name, p.phase)).collect::<Vec<_>>())
+            .field("disabled", &self.disabled)
+            .finish()
+    }
+}
+
+impl PassRegistry {
+    pub(crate) fn is_enabled(&self, name: &str) -> bool {
+        !self.disabled.contains(name)
+    }
+
+    /// 内置 pass, 关闭时为 noop
+    pub(crate) fn builtin<P: Pass + 'static>(&self, name: &str, pass: P) -> Box<dyn Pass> {
+        if self.is_enabled(name) { Box::new(pass) } else { Box::new(noop_pass()) }
+    }
+
+    /// 按注册顺序创建 `phase` 的自定义 passes, 在内置 passes 之后执行
+    pub(crate) fn custom(
+        &self,
+        phase: PassPhase,
+        file: &Path,
+        options: &BundlessOptions,
+    ) -> CustomPasses {
+        CustomPasses(
+            self.custom
+                .iter()
+                .filter(|pass| pass.phase == phase && self.is_enabled(&pass.name))
+                .map(|pass| (pass.factory)(file, options))
+                .collect(),
+        )
+    }
+}
+
+pub(crate) struct CustomPasses(Vec<Box<dyn Pass>>);
+
+impl Pass for CustomPasses {
+    fn process(&mut self, program: &mut Program) {
+        for pass in &mut self.0 {
+            pass.process(program);
+        }
+    }
+}
+
+/// 为 [BundlessOptions] 注册自定义 swc passes, 供嵌入 lecp_bundless 的 crate 使用
+///
+/// ```ignore
+/// let options = BundlessBuilder::new(BundlessOptions::default())
+///     .after_pass("my-pass", |_file, _options| Box::new(visit_mut_pass(MyVisitor)))
+///     .disable_pass("shims")
+///     .build();
+///
+/// bundless_files(&options)?;
+/// ```
+pub struct BundlessBuilder {
+    options: BundlessOptions,
+}
+
+impl BundlessBuilder {
+    pub fn new(options: BundlessOptions) -> Self {
+        Self { options }
+    }
+
+    pub fn before_pass<F>(self, name: &str, factory: F) -> Self
+    where
+        F: Fn(&Path, &BundlessOptions) -> Box<dyn Pass> + Send + Sync + 'static,
+    {
+        self.pass(name, PassPhase::Before, Arc::new(factory))
+    }
+
+    pub fn after_pass<F>(self, name: &str, factory: F) -> Self
+    where
+        F: Fn(&Path, &BundlessOptions) -> Box<dyn Pass> + Send + Sync + 'static,
+    {
+        self.pass(name, PassPhase::After, Arc::new(factory))
+    }
+
+    pub fn pass(mut self, name: &str, phase: PassPhase, factory: Arc<PassFactory>) -> Self {
+        self.options.passes.custom.push(CustomPass { name: name.to_string(), phase, factory });
+        self
+    }
+
+    /// 关闭内置 pass ([BEFORE_PASSES], [AFTER_PASSES]) 或已注册的自定义 pass
+    pub fn disable_pass(mut self, name: &str) -> Self {
+        self.options.passes.disabled.insert(name.to_string());
+        self
+    }
+
+    /// 构建钩子, 见 [BundlessHooks]
+    pub fn hooks(mut self, hooks: Arc<dyn BundlessHooks>) -> Self {
+        self.options.hooks = Hooks::new(hooks);
+        self
+    }
+
+    pub fn build(self) -> BundlessOptions {
+        self.options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use swc_core::ecma::ast::Ident;
+    use swc_core::ecma::visit::{VisitMut, visit_mut_pass};
+
+    use super::*;
+    use crate::swc::transform_file;
+    use crate::{ModuleType, Shims};
+
+    struct Rename;
+
+    impl VisitMut for Rename {
+        fn visit_mut_ident(&mut self, i: &mut Ident) {
+            if i.sym.as_ref() == "foo" {
+                i.sym = "bar".into();
+            }
+        }
+    }
+
+    fn fixture(path: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/builder").join(path)
+    }
+
+    fn transform(options: &BundlessOptions) -> String {
+        transform_fixture("src/index.ts", options)
+    }
+
+    fn transform_fixture(file: &str, options: &BundlessOptions) -> String {
+        let file = fixture(file);
+        let swc_options = options.build_for_swc().unwrap();
+        transform_file(&file, None, &swc_options, options).unwrap().code
+    }
+
+    #[test]
+    fn test_custom_pass() {
+        let options = BundlessOptions::default()
+            .cwd(fixture(""))
+            .format(ModuleType::ESM)
+            .targets(serde_json::json!({ "node": "22" }))
+            .shims(Shims::Boolean(true));
+
+        let code = transform(&options);
+        assert!(code.contains("foo"));
+        assert!(code.contains("import.meta.dirname"));
+
+        let options = BundlessBuilder::new(options)
+            .after_pass("rename", |_, _| Box::new(visit_mut_pass(Rename)))
+            .disable_pass("shims")
+            .build();
+
+        let code = transform(&options);
+        assert!(!code.contains("foo"));
+        assert!(code.contains("bar"));
+        assert!(code.contains("__dirname"));
+
+        // 关闭自定义 pass
+        let code = transform(&BundlessBuilder::new(options).disable_pass("rename").build());
+        assert!(code.contains("foo"));
+    }
+
+    // 每个内置 pass 名称只控制一项转换
+    #[test]
+    fn test_disable_builtin_pass() {
+        let options = BundlessOptions::default()
+            .cwd(fixture(""))
+            .format(ModuleType::ESM)
+            .targets(serde_json::json!({ "node": "22" }));
+
+        let code = transform_fixture("src/alias.ts", &options);
+        assert!(!code.contains("@/utils"));
+        assert_eq!(code.matches("\"./utils.mjs\"").count(), 2, "{code}");
+
+        // alias 关闭: 仍补全后缀
+        let code = transform_fixture(
+            "src/alias.ts",
+            &BundlessBuilder::new(options.clone()).disable_pass("alias").build(),
+        );
+        assert!(code.contains("\"@/utils\""));
+        assert!(code.contains("\"./utils.mjs\""));
+
+        // resolve-extensions 关闭: 仍处理 alias
+        let code = transform_fixture(
+            "src/alias.ts",
+            &BundlessBuilder::new(options).disable_pass("resolve-extensions").build(),
+        );
+        assert!(!code.contains("@/utils"));
+        assert_eq!(code.matches("\"./utils").count(), 2, "{code}");
+    }
+}
@@ -0,0 +1,52 @@
+use log::warn;
+use owo_colors::OwoColorize;
+use serde_json::{Value, json};
+use swc_transform_define::conflicting_keys;
+
+use crate::BundlessOptions;
+
+/// User `define` entries, replaced by swc `jsc.transform.optimizer.globals`
+///
+/// webpack define 的值为代码字符串, 可以解析为 JSON 时取解析后的值 (用于检查冲突), 否则保留原字符串
+pub(crate) fn user_define(options: &BundlessOptions) -> Value {
+    let mut define = serde_json::Map::new();
+
+    if let Some(user_define) = &options.define {
+        for (k, v) in &user_define.variables {
+            define.insert(k.clone(), serde_json::from_str(v).unwrap_or_else(|_| json!(v)));
+        }
+    }
+
+    Value::Object(define)
+}
+
+/// `swc_transform_define::collect_stats` keys
+pub(crate) fn user_define_keys(options: &BundlessOptions) -> Vec<String> {
+    options
+        .define
+        .as_ref()
+        .map(|define| define.variables.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Warn about overlapping define keys, e.g. `process.env.NODE_ENV` and `process`
+pub(crate) fn report_define_conflicts(options: &BundlessOptions) {
+    for (key, shadowed) in conflicting_keys(&user_define(options)) {
+        warn!(
+            "define key {} overlaps {}, {} takes precedence",
+            key.yellow(),
+            shadowed.yellow(),
+            key.yellow()
+        );
+    }
+}
+
+/// Warn about define keys that matched zero references across the build
+pub(crate) fn report_unused_define(options: &BundlessOptions) {
+    let define = user_define(options);
+    let stats = options.define_stats.lock().unwrap();
+
+    for key in stats.unused_keys(&define) {
+        warn!("define key {} is never referenced", key.yellow());
+    }
+}
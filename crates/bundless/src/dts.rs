@@ -0,0 +1,374 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result, anyhow};
+use log::warn;
+use miette::{LabeledSpan, NamedSource, miette};
+use owo_colors::OwoColorize;
+use serde_json::json;
+use swc_core::common::comments::SingleThreadedComments;
+use swc_core::common::source_map::DefaultSourceMapGenConfig;
+use swc_core::common::{GLOBALS, Mark, SourceFile, SourceMap, Span};
+use swc_core::ecma::ast::{self, EsVersion, Program};
+use swc_core::ecma::codegen::text_writer::JsWriter;
+use swc_core::ecma::codegen::{Config as CodegenConfig, Emitter};
+use swc_core::ecma::parser::{Syntax, TsSyntax, parse_file_as_module};
+use swc_core::ecma::transforms::base::resolver;
+use swc_transform_extensions::ResolvedSpecifiers;
+use swc_typescript::diagnostic::DtsIssue;
+use swc_typescript::fast_dts::FastDts;
+
+use crate::BundlessOptions;
+use crate::swc::{RESOLVE_EXTENSIONS, extensions_config};
+
+#[derive(Debug, Default)]
+pub struct DtsOutput {
+    pub code: String,
+    /// `.d.ts.map`, only when `sourcemap` is enabled
+    pub map: Option<String>,
+    /// isolated declarations 诊断 (缺少显式类型注解等)
+    pub diagnostics: Option<miette::Report>,
+    /// 需要补充类型注解的导出
+    pub missing_annotations: Vec<String>,
+}
+
+/// Isolated declarations of `file` (swc fast dts)
+///
+/// 与 `jsc.experimental.emitIsolatedDts` 相同, 但保留源文件的 span 以生成 `.d.ts.map`,
+/// 并重写 import specifiers (alias, ts2js, extensions), 与 js 产物保持一致
+pub fn transform_dts(file: &Path, options: &BundlessOptions) -> Result<DtsOutput> {
+    let cm = Arc::<SourceMap>::default();
+    let comments = SingleThreadedComments::default();
+
+    let (program, diagnostics, missing_annotations) =
+        GLOBALS.set(&Default::default(), || -> Result<_> {
+            let dts = isolated_dts(&cm, &comments, file)?;
+            let program = rewrite_dts_specifiers(Program::Module(dts.module), file, options)?;
+
+            Ok((program, dts.diagnostics, dts.missing_annotations))
+        })?;
+
+    let mut buf = vec![];
+    let mut src_map_buf = vec![];
+    {
+        let mut emitter = Emitter {
+            cfg: CodegenConfig::default(),
+            cm: cm.clone(),
+            comments: Some(&comments),
+            wr: JsWriter::new(
+                cm.clone(),
+                "\n",
+                &mut buf,
+                options.sourcemap.then_some(&mut src_map_buf),
+            ),
+        };
+        emitter.emit_program(&program)?;
+    }
+
+    let map = if options.sourcemap {
+        let mut map_buf = vec![];
+        cm.build_source_map(&src_map_buf, None, DefaultSourceMapGenConfig)
+            .to_writer(&mut map_buf)
+            .context("failed to write declaration source map")?;
+        Some(String::from_utf8(map_buf)?)
+    } else {
+        None
+    };
+
+    Ok(DtsOutput { code: String::from_utf8(buf)?, map, diagnostics, missing_annotations })
+}
+
+/// Isolated declarations of a single file, specifiers untouched
+pub(crate) struct IsolatedDts {
+    pub module: ast::Module,
+    /// 用于生成新的顶层标识符 (如匿名 `export default`)
+    pub top_level_mark: Mark,
+    pub diagnostics: Option<miette::Report>,
+    pub missing_annotations: Vec<String>,
+}
+
+/// Parse `file` and run swc fast dts, must be called inside `GLOBALS`
+///
+/// `cm` 与 `comments` 可在多个文件间共享 (dts bundle)
+pub(crate) fn isolated_dts(
+    cm: &Arc<SourceMap>,
+    comments: &SingleThreadedComments,
+    file: &Path,
+) -> Result<IsolatedDts> {
+    let fm =
+        cm.load_file(file).with_context(|| format!("swc failed to load {}", file.display()))?;
+
+    let is_tsx = file.extension().is_some_and(|ext| ext == "tsx" || ext == "jsx");
+    let is_dts = file.to_string_lossy().ends_with(".d.ts");
+    let syntax = Syntax::Typescript(TsSyntax {
+        tsx: is_tsx,
+        decorators: true,
+        dts: is_dts,
+        ..Default::default()
+    });
+
+    let module =
+        parse_file_as_module(&fm, syntax, EsVersion::latest(), Some(comments), &mut vec![])
+            .map_err(|e| anyhow!("failed to parse {}: {}", file.display(), e.kind().msg()))?;
+
+    // FastDts 会移除实现, 先记录导出声明的范围
+    let exports = export_spans(&module);
+
+    let unresolved_mark = Mark::new();
+    let top_level_mark = Mark::new();
+
+    let mut program =
+        Program::Module(module).apply(resolver(unresolved_mark, top_level_mark, true));
+
+    let issues =
+        FastDts::new(fm.name.clone(), unresolved_mark, Default::default()).transform(&mut program);
+
+    let (diagnostics, missing_annotations) = dts_diagnostics(file, &fm, &issues, &exports);
+
+    let module = program.expect_module();
+
+    Ok(IsolatedDts { module, top_level_mark, diagnostics, missing_annotations })
+}
+
+/// 导出名称与对应声明的范围
+/// - `export function foo() {}` -> `foo`
+/// - `export const a = 1, b = 2` -> `a`, `b`
+/// - `export default ...` -> `default`
+fn export_spans(module: &ast::Module) -> Vec<(String, Span)> {
+    let mut exports = vec![];
+
+    for item in &module.body {
+        let ast::ModuleItem::ModuleDecl(decl) = item else {
+            continue;
+        };
+
+        match decl {
+            ast::ModuleDecl::ExportDecl(export) => match &export.decl {
+                ast::Decl::Fn(f) => exports.push((f.ident.sym.to_string(), export.span)),
+                ast::Decl::Class(c) => exports.push((c.ident.sym.to_string(), export.span)),
+                ast::Decl::Var(var) => {
+                    for decl in &var.decls {
+                        if let ast::Pat::Ident(ident) = &decl.name {
+                            exports.push((ident.id.sym.to_string(), decl.span));
+                        }
+                    }
+                }
+                _ => {}
+            },
+            ast::ModuleDecl::ExportDefaultDecl(export) => {
+                exports.push(("default".to_string(), export.span))
+            }
+            ast::ModuleDecl::ExportDefaultExpr(export) => {
+                exports.push(("default".to_string(), export.span))
+            }
+            _ => {}
+        }
+    }
+
+    exports
+}
+
+/// isolated declarations issues -> miette report (带源码位置)
+fn dts_diagnostics(
+    file: &Path,
+    fm: &SourceFile,
+    issues: &[DtsIssue],
+    exports: &[(String, Span)],
+) -> (Option<miette::Report>, Vec<String>) {
+    if issues.is_empty() {
+        return (None, vec![]);
+    }
+
+    let mut missing_annotations: Vec<String> = vec![];
+
+    let labels = issues
+        .iter()
+        .map(|issue| {
+            let span = issue.range.span;
+            let export = exports
+                .iter()
+                .find(|(_, export_span)| export_span.contains(span))
+                .map(|(name, _)| name.clone());
+
+            let message = match &export {
+                Some(name) => {
+                    if !missing_annotations.contains(name) {
+                        missing_annotations.push(name.clone());
+                    }
+                    format!("{} (export `{}`)", issue.message, name)
+                }
+                None => issue.message.to_string(),
+            };
+
+            // dummy span (或不在当前文件) 无法定位, 标注在文件开头
+            let offset = if span.is_dummy() { None } else { span.lo.0.checked_sub(fm.start_pos.0) };
+            match offset {
+                Some(offset) => {
+                    let len = span.hi.0.saturating_sub(span.lo.0);
+                    LabeledSpan::at(offset as usize..(offset + len) as usize, message)
+                }
+                None => LabeledSpan::at_offset(0, message),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let help = if missing_annotations.is_empty() {
+        "add explicit type annotations".to_string()
+    } else {
+        format!("add explicit type annotations to: {}", missing_annotations.join(", "))
+    };
+
+    let report = miette!(
+        labels = labels,
+        help = help,
+        "isolated declarations failed for {}",
+        file.display()
+    )
+    .with_source_code(NamedSource::new(file.display().to_string(), fm.src.to_string()));
+
+    (Some(report), missing_annotations)
+}
+
+/// `dts.strict` 时诊断视为错误, 否则仅 warn
+pub(crate) fn report_dts_diagnostics(
+    output: &mut DtsOutput,
+    options: &BundlessOptions,
+) -> Result<()> {
+    let Some(report) = output.diagnostics.take() else {
+        return Ok(());
+    };
+
+    if options.dts.strict {
+        return Err(anyhow!("{:?}", report));
+    }
+
+    warn!(
+        "{:?}\nexports need explicit type annotations: {}",
+        report,
+        output.missing_annotations.join(", ").yellow()
+    );
+
+    Ok(())
+}
+
+/// Rewrite import specifiers of isolated declarations, same as the js output
+/// - alias: `@/utils` -> `./utils`
+/// - resolve extensions: `./utils` -> `./utils.mjs`, `./Button` -> `./Button.js` (Button.tsx)
+/// - ts2js: `./foo.ts` -> `./foo.js`
+/// - extensions: `./foo.js` -> `./foo.mjs` (已补全的不再替换)
+fn rewrite_dts_specifiers(
+    program: Program,
+    file: &Path,
+    options: &BundlessOptions,
+) -> Result<Program> {
+    let config = extensions_config(file, options)?;
+    let resolve_config = swc_transform_extensions::Config {
+        resolve_extensions: RESOLVE_EXTENSIONS.map(String::from).to_vec(),
+        ..config.clone()
+    };
+    // alias 仅处理一次, 避免替换后的路径再次匹配
+    let alias_config = swc_transform_extensions::Config {
+        alias: options.alias_rules()?,
+        ..resolve_config.clone()
+    };
+    let resolved = ResolvedSpecifiers::default();
+
+    Ok(program.apply((
+        swc_transform_extensions::alias(alias_config),
+        swc_transform_extensions::resolve_extensions(resolve_config, resolved.clone()),
+        swc_transform_ts2js::transform(Default::default()),
+        swc_transform_extensions::transform_with_resolved(config, resolved),
+    )))
+}
+
+/// 修正 `.d.ts.map`
+/// - file: `index.d.ts` (`.d.mts`, `.d.cts`)
+/// - sources: 相对于 `.d.ts` 所在目录
+pub(crate) fn fix_dts_source_map(map: &str, dts_path: &Path) -> Result<String> {
+    let mut map: serde_json::Value = serde_json::from_str(map)?;
+    let dts_dir = dts_path.parent().unwrap_or(Path::new(""));
+
+    if let Some(file_name) = dts_path.file_name() {
+        map["file"] = json!(file_name.to_string_lossy());
+    }
+
+    if let Some(sources) = map["sources"].as_array_mut() {
+        for source in sources.iter_mut() {
+            let relative = source
+                .as_str()
+                .and_then(|source| pathdiff::diff_paths(source, dts_dir))
+                .map(|rel_path| {
+                    rel_path
+                        .components()
+                        .map(|c| c.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/")
+                });
+
+            if let Some(relative) = relative {
+                *source = json!(relative);
+            }
+        }
+    }
+
+    Ok(map.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::Dts;
+
+    fn fixture(path: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/dts").join(path)
+    }
+
+    #[test]
+    fn test_transform_dts_specifiers() {
+        let options = BundlessOptions::default()
+            .cwd(fixture(""))
+            .is_module(false)
+            .targets(json!({ "node": "20.11.0" }));
+
+        let dts = transform_dts(&fixture("src/components/index.ts"), &options).unwrap();
+
+        assert!(dts.code.contains(r#"from "../utils/index.mjs""#));
+        assert!(dts.code.contains(r#"from "./button.mjs""#));
+        assert!(dts.code.contains(r#"import("./button.mjs")"#));
+        assert!(dts.map.is_none());
+    }
+
+    #[test]
+    fn test_transform_dts_sourcemap() {
+        let options = BundlessOptions::default().cwd(fixture("")).sourcemap(true);
+        let file = fixture("src/utils/index.ts");
+
+        let dts = transform_dts(&file, &options).unwrap();
+        let map = fix_dts_source_map(&dts.map.unwrap(), &fixture("es/utils/index.d.ts")).unwrap();
+        let map: serde_json::Value = serde_json::from_str(&map).unwrap();
+
+        assert_eq!(map["file"], "index.d.ts");
+        assert_eq!(map["sources"], json!(["../../src/utils/index.ts"]));
+    }
+
+    #[test]
+    fn test_transform_dts_diagnostics() {
+        let options = BundlessOptions::default().cwd(fixture(""));
+        let file = fixture("src/untyped.ts");
+
+        let mut dts = transform_dts(&file, &options).unwrap();
+        assert!(dts.diagnostics.is_some());
+        assert_eq!(dts.missing_annotations, vec!["add", "value"]);
+        assert!(report_dts_diagnostics(&mut dts, &options).is_ok());
+
+        let options = options.dts(Dts::default().strict(true));
+        let mut dts = transform_dts(&file, &options).unwrap();
+        assert!(report_dts_diagnostics(&mut dts, &options).is_err());
+
+        let mut dts = transform_dts(&fixture("src/utils/index.ts"), &options).unwrap();
+        assert!(dts.diagnostics.is_none());
+        assert!(report_dts_diagnostics(&mut dts, &options).is_ok());
+    }
+}
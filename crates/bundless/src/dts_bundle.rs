@@ -0,0 +1,704 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow, bail};
+use log::{debug, warn};
+use owo_colors::OwoColorize;
+use swc_core::common::comments::SingleThreadedComments;
+use swc_core::common::{DUMMY_SP, GLOBALS, Mark, SourceMap, SyntaxContext};
+use swc_core::ecma::ast::{self, Id};
+use swc_core::ecma::codegen::text_writer::JsWriter;
+use swc_core::ecma::codegen::{Config as CodegenConfig, Emitter};
+use swc_core::ecma::utils::find_pat_ids;
+use swc_core::ecma::visit::{VisitMut, VisitMutWith};
+
+use crate::BundlessOptions;
+use crate::dts::{DtsOutput, isolated_dts, report_dts_diagnostics};
+use crate::swc::RESOLVE_EXTENSIONS;
+
+/// Bundle the isolated declarations reachable from `entry` into a single `.d.ts`
+///
+/// - 跟随相对路径 (及 alias) 的 import / export from, 合并为一个模块
+/// - 外部包的 import 保留并去重
+/// - 不同文件的同名声明重命名: `Config` -> `Config$1`
+pub fn bundle_dts(entry: &Path, options: &BundlessOptions) -> Result<String> {
+    let cm = Arc::<SourceMap>::default();
+    let comments = SingleThreadedComments::default();
+
+    let program = GLOBALS.set(&Default::default(), || -> Result<_> {
+        let mut bundler = DtsBundler::new(&cm, &comments, options);
+        let entry = bundler.load(entry)?;
+        Ok(bundler.bundle(entry))
+    })?;
+
+    let mut buf = vec![];
+    {
+        let mut emitter = Emitter {
+            cfg: CodegenConfig::default(),
+            cm: cm.clone(),
+            comments: Some(&comments),
+            wr: JsWriter::new(cm.clone(), "\n", &mut buf, None),
+        };
+        emitter.emit_program(&program)?;
+    }
+
+    Ok(String::from_utf8(buf)?)
+}
+
+// import { a as b } from "pkg" 的 key: (src, imported)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ExternalImport {
+    Named(String, String),
+    Default(String),
+    Namespace(String),
+}
+
+#[derive(Debug, Default)]
+struct DtsModule {
+    /// 去掉 import / export 之后的声明
+    items: Vec<ast::ModuleItem>,
+    /// 顶层声明
+    decls: Vec<Id>,
+    /// export name -> local
+    local_exports: Vec<(String, Id)>,
+    /// export { name } from "./other"
+    reexports: Vec<(String, (usize, String))>,
+    /// export * from "./other"
+    star_exports: Vec<usize>,
+    /// export * from "pkg"
+    external_star_exports: Vec<String>,
+    /// import { name as local } from "./other"
+    imports: HashMap<Id, (usize, String)>,
+}
+
+struct DtsBundler<'a> {
+    cm: &'a Arc<SourceMap>,
+    comments: &'a SingleThreadedComments,
+    options: &'a BundlessOptions,
+    modules: Vec<DtsModule>,
+    indexes: HashMap<PathBuf, usize>,
+    /// 依赖在前
+    order: Vec<usize>,
+    externals: Vec<(ExternalImport, Id)>,
+    /// 重复的外部 import -> 首次出现的 local
+    external_alias: HashMap<Id, Id>,
+    /// import "pkg"
+    side_effects: Vec<String>,
+}
+
+impl<'a> DtsBundler<'a> {
+    fn new(
+        cm: &'a Arc<SourceMap>,
+        comments: &'a SingleThreadedComments,
+        options: &'a BundlessOptions,
+    ) -> Self {
+        Self {
+            cm,
+            comments,
+            options,
+            modules: vec![],
+            indexes: HashMap::new(),
+            order: vec![],
+            externals: vec![],
+            external_alias: HashMap::new(),
+            side_effects: vec![],
+        }
+    }
+
+    fn load(&mut self, file: &Path) -> Result<usize> {
+        if let Some(&index) = self.indexes.get(file) {
+            return Ok(index);
+        }
+
+        debug!("bundle dts: {}", file.display());
+
+        let dts = isolated_dts(self.cm, self.comments, file)?;
+        report_dts_diagnostics(
+            &mut DtsOutput {
+                diagnostics: dts.diagnostics,
+                missing_annotations: dts.missing_annotations,
+                ..Default::default()
+            },
+            self.options,
+        )?;
+
+        let current_dir = file.parent().map(Path::to_path_buf).unwrap_or_default();
+        // 仅处理 alias: `@/utils` -> `../utils`
+        let alias_pass = swc_transform_extensions::transform(swc_transform_extensions::Config {
+            current_dir: Some(current_dir.to_string_lossy().to_string()),
+            alias: self.options.alias_rules()?,
+            ..Default::default()
+        });
+        let module = ast::Program::Module(dts.module).apply(alias_pass).expect_module();
+
+        // 先占位, 循环依赖时直接返回 index
+        let index = self.modules.len();
+        self.indexes.insert(file.to_path_buf(), index);
+        self.modules.push(DtsModule::default());
+
+        let top_level_ctxt = SyntaxContext::empty().apply_mark(dts.top_level_mark);
+        let mut m = DtsModule::default();
+
+        for item in module.body {
+            let decl = match item {
+                ast::ModuleItem::ModuleDecl(decl) => decl,
+                ast::ModuleItem::Stmt(stmt) => {
+                    if let ast::Stmt::Decl(decl) = &stmt {
+                        m.decls.extend(decl_ids(decl));
+                    }
+                    m.items.push(stmt.into());
+                    continue;
+                }
+            };
+
+            match decl {
+                ast::ModuleDecl::Import(import) => {
+                    let src = import.src.value.to_atom_lossy().to_string();
+
+                    if !is_relative(&src) {
+                        if import.specifiers.is_empty() && !self.side_effects.contains(&src) {
+                            self.side_effects.push(src);
+                        }
+
+                        for specifier in import.specifiers {
+                            let (key, local) = match specifier {
+                                ast::ImportSpecifier::Named(s) => {
+                                    let imported = s
+                                        .imported
+                                        .as_ref()
+                                        .map_or_else(|| s.local.sym.to_string(), export_name);
+                                    (ExternalImport::Named(src.clone(), imported), s.local)
+                                }
+                                ast::ImportSpecifier::Default(s) => {
+                                    (ExternalImport::Default(src.clone()), s.local)
+                                }
+                                ast::ImportSpecifier::Namespace(s) => {
+                                    (ExternalImport::Namespace(src.clone()), s.local)
+                                }
+                            };
+                            self.add_external(key, local.to_id());
+                        }
+                        continue;
+                    }
+
+                    let target = self.load(&resolve_source(&src, &current_dir)?)?;
+
+                    for specifier in import.specifiers {
+                        match specifier {
+                            ast::ImportSpecifier::Named(s) => {
+                                let imported = s
+                                    .imported
+                                    .as_ref()
+                                    .map_or_else(|| s.local.sym.to_string(), export_name);
+                                m.imports.insert(s.local.to_id(), (target, imported));
+                            }
+                            ast::ImportSpecifier::Default(s) => {
+                                m.imports.insert(s.local.to_id(), (target, "default".to_string()));
+                            }
+                            ast::ImportSpecifier::Namespace(_) => {
+                                bail!(
+                                    "dts bundle does not support namespace import of \"{src}\" in {}",
+                                    file.display()
+                                );
+                            }
+                        }
+                    }
+                }
+                ast::ModuleDecl::ExportDecl(export) => {
+                    for id in decl_ids(&export.decl) {
+                        m.local_exports.push((id.0.to_string(), id.clone()));
+                        m.decls.push(id);
+                    }
+                    m.items.push(ast::Stmt::Decl(export.decl).into());
+                }
+                ast::ModuleDecl::ExportDefaultDecl(export) => {
+                    let default_ident = |ident: Option<ast::Ident>| {
+                        ident.unwrap_or_else(|| {
+                            ast::Ident::new("_default".into(), DUMMY_SP, top_level_ctxt)
+                        })
+                    };
+
+                    let decl = match export.decl {
+                        ast::DefaultDecl::Class(c) => ast::Decl::Class(ast::ClassDecl {
+                            ident: default_ident(c.ident),
+                            declare: true,
+                            class: c.class,
+                        }),
+                        ast::DefaultDecl::Fn(f) => ast::Decl::Fn(ast::FnDecl {
+                            ident: default_ident(f.ident),
+                            declare: true,
+                            function: f.function,
+                        }),
+                        ast::DefaultDecl::TsInterfaceDecl(i) => ast::Decl::TsInterface(i),
+                    };
+
+                    for id in decl_ids(&decl) {
+                        m.local_exports.push(("default".to_string(), id.clone()));
+                        m.decls.push(id);
+                    }
+                    m.items.push(ast::Stmt::Decl(decl).into());
+                }
+                // isolated declarations: `declare const _default: T; export default _default;`
+                ast::ModuleDecl::ExportDefaultExpr(export) => match *export.expr {
+                    ast::Expr::Ident(ident) => {
+                        m.local_exports.push(("default".to_string(), ident.to_id()));
+                    }
+                    _ => bail!("dts bundle: unsupported export default in {}", file.display()),
+                },
+                ast::ModuleDecl::ExportNamed(export) => {
+                    let src = export.src.as_ref().map(|src| src.value.to_atom_lossy().to_string());
+                    let target = match &src {
+                        Some(src) if is_relative(src) => {
+                            Some(self.load(&resolve_source(src, &current_dir)?)?)
+                        }
+                        _ => None,
+                    };
+
+                    for specifier in export.specifiers {
+                        match (specifier, &src, target) {
+                            // export { a as b }
+                            (ast::ExportSpecifier::Named(s), None, _) => {
+                                let ast::ModuleExportName::Ident(orig) = &s.orig else {
+                                    continue;
+                                };
+                                let exported = s
+                                    .exported
+                                    .as_ref()
+                                    .map_or_else(|| orig.sym.to_string(), export_name);
+                                m.local_exports.push((exported, orig.to_id()));
+                            }
+                            // export { a as b } from "./other"
+                            (ast::ExportSpecifier::Named(s), Some(_), Some(target)) => {
+                                let orig = export_name(&s.orig);
+                                let exported =
+                                    s.exported.as_ref().map_or_else(|| orig.clone(), export_name);
+                                m.reexports.push((exported, (target, orig)));
+                            }
+                            // export { a as b } from "pkg"
+                            (ast::ExportSpecifier::Named(s), Some(src), None) => {
+                                let orig = export_name(&s.orig);
+                                let exported =
+                                    s.exported.as_ref().map_or_else(|| orig.clone(), export_name);
+                                let local = fresh_id(&orig);
+                                self.add_external(
+                                    ExternalImport::Named(src.clone(), orig),
+                                    local.clone(),
+                                );
+                                m.local_exports.push((exported, local));
+                            }
+                            // export * as ns from "pkg"
+                            (ast::ExportSpecifier::Namespace(s), Some(src), None) => {
+                                let exported = export_name(&s.name);
+                                let local = fresh_id(&exported);
+                                self.add_external(
+                                    ExternalImport::Namespace(src.clone()),
+                                    local.clone(),
+                                );
+                                m.local_exports.push((exported, local));
+                            }
+                            _ => bail!(
+                                "dts bundle: unsupported export from \"{}\" in {}",
+                                src.as_deref().unwrap_or_default(),
+                                file.display()
+                            ),
+                        }
+                    }
+                }
+                ast::ModuleDecl::ExportAll(export) => {
+                    let src = export.src.value.to_atom_lossy().to_string();
+
+                    if is_relative(&src) {
+                        let target = self.load(&resolve_source(&src, &current_dir)?)?;
+                        m.star_exports.push(target);
+                    } else {
+                        m.external_star_exports.push(src);
+                    }
+                }
+                ast::ModuleDecl::TsImportEquals(import) => {
+                    m.decls.push(import.id.to_id());
+                    m.items.push(ast::ModuleDecl::TsImportEquals(import).into());
+                }
+                ast::ModuleDecl::TsExportAssignment(_) => {
+                    bail!("dts bundle does not support `export =` in {}", file.display());
+                }
+                decl => m.items.push(decl.into()),
+            }
+        }
+
+        self.modules[index] = m;
+        self.order.push(index);
+
+        Ok(index)
+    }
+
+    fn add_external(&mut self, key: ExternalImport, local: Id) {
+        match self.externals.iter().find(|(k, _)| *k == key) {
+            Some((_, canonical)) => {
+                self.external_alias.insert(local, canonical.clone());
+            }
+            None => self.externals.push((key, local)),
+        }
+    }
+
+    /// export name -> 最终声明
+    fn resolve_export(
+        &self,
+        module: usize,
+        name: &str,
+        visited: &mut HashSet<(usize, String)>,
+    ) -> Option<Id> {
+        if !visited.insert((module, name.to_string())) {
+            return None;
+        }
+
+        let m = &self.modules[module];
+
+        if let Some((_, local)) = m.local_exports.iter().find(|(n, _)| n == name) {
+            return self.resolve_local(module, local, visited);
+        }
+
+        if let Some((_, (target, orig))) = m.reexports.iter().find(|(n, _)| n == name) {
+            return self.resolve_export(*target, orig, visited);
+        }
+
+        // export * 不包含 default
+        if name == "default" {
+            return None;
+        }
+
+        m.star_exports.iter().find_map(|&target| self.resolve_export(target, name, visited))
+    }
+
+    fn resolve_local(
+        &self,
+        module: usize,
+        local: &Id,
+        visited: &mut HashSet<(usize, String)>,
+    ) -> Option<Id> {
+        if let Some((target, imported)) = self.modules[module].imports.get(local) {
+            return self.resolve_export(*target, imported, visited);
+        }
+
+        Some(self.external_alias.get(local).unwrap_or(local).clone())
+    }
+
+    fn export_names(&self, module: usize, visited: &mut HashSet<usize>, names: &mut Vec<String>) {
+        if !visited.insert(module) {
+            return;
+        }
+
+        let m = &self.modules[module];
+        let local_names =
+            m.local_exports.iter().map(|(n, _)| n).chain(m.reexports.iter().map(|(n, _)| n));
+
+        for name in local_names {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+
+        for &target in &m.star_exports {
+            let mut star_names = vec![];
+            self.export_names(target, visited, &mut star_names);
+
+            for name in star_names {
+                if name != "default" && !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+    }
+
+    fn external_star_exports(&self, module: usize, visited: &mut HashSet<usize>) -> Vec<String> {
+        if !visited.insert(module) {
+            return vec![];
+        }
+
+        let m = &self.modules[module];
+        let mut srcs = m.external_star_exports.clone();
+        for &target in &m.star_exports {
+            srcs.extend(self.external_star_exports(target, visited));
+        }
+
+        srcs
+    }
+
+    fn bundle(self, entry: usize) -> ast::Program {
+        // 分配不冲突的名称: 外部 import 优先, 其余按依赖顺序
+        let mut used = HashSet::new();
+        let mut names: HashMap<Id, String> = HashMap::new();
+
+        let declared = self
+            .externals
+            .iter()
+            .map(|(_, id)| id)
+            .chain(self.order.iter().flat_map(|&index| self.modules[index].decls.iter()));
+
+        for id in declared {
+            if names.contains_key(id) {
+                continue;
+            }
+
+            let mut name = id.0.to_string();
+            let mut i = 1;
+            while used.contains(&name) {
+                name = format!("{}${}", id.0, i);
+                i += 1;
+            }
+
+            used.insert(name.clone());
+            names.insert(id.clone(), name);
+        }
+
+        // import 的 local 指向最终声明
+        let mut renames = names.clone();
+        for (index, m) in self.modules.iter().enumerate() {
+            for local in m.imports.keys() {
+                match self.resolve_local(index, local, &mut HashSet::new()) {
+                    Some(id) if names.contains_key(&id) => {
+                        renames.insert(local.clone(), names[&id].clone());
+                    }
+                    _ => warn!("dts bundle: cannot resolve import {}", local.0.yellow()),
+                }
+            }
+        }
+        for (local, canonical) in &self.external_alias {
+            renames.insert(local.clone(), names[canonical].clone());
+        }
+
+        let mut entry_names = vec![];
+        self.export_names(entry, &mut HashSet::new(), &mut entry_names);
+
+        let entry_exports = entry_names
+            .into_iter()
+            .filter_map(|name| match self.resolve_export(entry, &name, &mut HashSet::new()) {
+                Some(id) => {
+                    let local = names.get(&id).cloned().unwrap_or_else(|| id.0.to_string());
+                    Some((name, local))
+                }
+                None => {
+                    warn!("dts bundle: cannot resolve export {}", name.yellow());
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        let external_star_exports = self.external_star_exports(entry, &mut HashSet::new());
+
+        let mut body: Vec<ast::ModuleItem> =
+            self.side_effects.iter().map(|src| import_decl(src, vec![])).collect();
+
+        for (key, id) in &self.externals {
+            let local = ast::Ident::new_no_ctxt(names[id].as_str().into(), DUMMY_SP);
+            let (src, specifier) = match key {
+                ExternalImport::Named(src, imported) => {
+                    let imported = (*imported != names[id]).then(|| {
+                        ast::ModuleExportName::Ident(ast::Ident::new_no_ctxt(
+                            imported.as_str().into(),
+                            DUMMY_SP,
+                        ))
+                    });
+                    (
+                        src,
+                        ast::ImportSpecifier::Named(ast::ImportNamedSpecifier {
+                            span: DUMMY_SP,
+                            local,
+                            imported,
+                            is_type_only: false,
+                        }),
+                    )
+                }
+                ExternalImport::Default(src) => (
+                    src,
+                    ast::ImportSpecifier::Default(ast::ImportDefaultSpecifier {
+                        span: DUMMY_SP,
+                        local,
+                    }),
+                ),
+                ExternalImport::Namespace(src) => (
+                    src,
+                    ast::ImportSpecifier::Namespace(ast::ImportStarAsSpecifier {
+                        span: DUMMY_SP,
+                        local,
+                    }),
+                ),
+            };
+
+            body.push(import_decl(src, vec![specifier]));
+        }
+
+        let mut rename = Rename { names: renames };
+        let mut modules = self.modules.into_iter().map(|m| m.items).collect::<Vec<_>>();
+        for index in self.order {
+            let mut items = std::mem::take(&mut modules[index]);
+            items.visit_mut_with(&mut rename);
+            body.extend(items);
+        }
+
+        let specifiers = entry_exports
+            .into_iter()
+            .map(|(exported, local)| {
+                let exported = (exported != local).then(|| {
+                    ast::ModuleExportName::Ident(ast::Ident::new_no_ctxt(
+                        exported.as_str().into(),
+                        DUMMY_SP,
+                    ))
+                });
+
+                ast::ExportSpecifier::Named(ast::ExportNamedSpecifier {
+                    span: DUMMY_SP,
+                    orig: ast::ModuleExportName::Ident(ast::Ident::new_no_ctxt(
+                        local.as_str().into(),
+                        DUMMY_SP,
+                    )),
+                    exported,
+                    is_type_only: false,
+                })
+            })
+            .collect();
+
+        // 没有导出时同样输出 `export {}`, 保证是模块
+        body.push(
+            ast::ModuleDecl::ExportNamed(ast::NamedExport {
+                span: DUMMY_SP,
+                specifiers,
+                src: None,
+                type_only: false,
+                with: None,
+            })
+            .into(),
+        );
+
+        for src in external_star_exports {
+            body.push(
+                ast::ModuleDecl::ExportAll(ast::ExportAll {
+                    span: DUMMY_SP,
+                    src: Box::new(src.into()),
+                    type_only: false,
+                    with: None,
+                })
+                .into(),
+            );
+        }
+
+        ast::Program::Module(ast::Module { span: DUMMY_SP, body, shebang: None })
+    }
+}
+
+struct Rename {
+    names: HashMap<Id, String>,
+}
+
+impl VisitMut for Rename {
+    fn visit_mut_ident(&mut self, n: &mut ast::Ident) {
+        if let Some(name) = self.names.get(&n.to_id()) {
+            n.sym = name.as_str().into();
+        }
+    }
+
+    // 导入类型 `import("./other")` 无法合并, 仅提示
+    fn visit_mut_ts_import_type(&mut self, n: &mut ast::TsImportType) {
+        n.visit_mut_children_with(self);
+
+        let arg = n.arg.value.to_atom_lossy().to_string();
+        if is_relative(&arg) {
+            warn!("dts bundle: import type {} is not inlined", arg.yellow());
+        }
+    }
+}
+
+fn decl_ids(decl: &ast::Decl) -> Vec<Id> {
+    match decl {
+        ast::Decl::Class(c) => vec![c.ident.to_id()],
+        ast::Decl::Fn(f) => vec![f.ident.to_id()],
+        ast::Decl::Var(v) => find_pat_ids(&v.decls),
+        ast::Decl::TsInterface(i) => vec![i.id.to_id()],
+        ast::Decl::TsTypeAlias(t) => vec![t.id.to_id()],
+        ast::Decl::TsEnum(e) => vec![e.id.to_id()],
+        ast::Decl::TsModule(m) => match &m.id {
+            ast::TsModuleName::Ident(ident) => vec![ident.to_id()],
+            // declare module "pkg" / declare global
+            ast::TsModuleName::Str(_) => vec![],
+        },
+        ast::Decl::Using(_) => vec![],
+    }
+}
+
+fn export_name(name: &ast::ModuleExportName) -> String {
+    match name {
+        ast::ModuleExportName::Ident(ident) => ident.sym.to_string(),
+        ast::ModuleExportName::Str(s) => s.value.to_atom_lossy().to_string(),
+    }
+}
+
+fn fresh_id(name: &str) -> Id {
+    (name.into(), SyntaxContext::empty().apply_mark(Mark::new()))
+}
+
+fn is_relative(specifier: &str) -> bool {
+    specifier.starts_with("./") || specifier.starts_with("../")
+}
+
+fn import_decl(src: &str, specifiers: Vec<ast::ImportSpecifier>) -> ast::ModuleItem {
+    ast::ModuleDecl::Import(ast::ImportDecl {
+        span: DUMMY_SP,
+        specifiers,
+        src: Box::new(src.into()),
+        type_only: false,
+        with: None,
+        phase: Default::default(),
+    })
+    .into()
+}
+
+// ./button, ./button.js, ./button.ts, ./components -> 源文件
+fn resolve_source(specifier: &str, current_dir: &Path) -> Result<PathBuf> {
+    let path = path_clean::clean(current_dir.join(specifier));
+    let path_str = path.to_string_lossy();
+
+    // ts2js: ./button.js -> ./button.ts
+    let base = [".js", ".mjs", ".cjs", ".jsx"]
+        .iter()
+        .find_map(|ext| path_str.strip_suffix(ext))
+        .unwrap_or(&path_str)
+        .to_string();
+
+    let extensions = RESOLVE_EXTENSIONS.iter().copied().chain([".d.ts"]);
+
+    let candidates = extensions
+        .clone()
+        .map(|ext| PathBuf::from(format!("{base}{ext}")))
+        .chain(std::iter::once(path.clone()))
+        .chain(extensions.map(|ext| path.join(format!("index{ext}"))));
+
+    for candidate in candidates {
+        if candidate.is_file() {
+            return Ok(candidate);
+        }
+    }
+
+    Err(anyhow!("dts bundle: cannot resolve \"{specifier}\" from {}", current_dir.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(path: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/dts-bundle").join(path)
+    }
+
+    #[test]
+    fn test_bundle_dts() {
+        let options = BundlessOptions::default().cwd(fixture(""));
+        let code = bundle_dts(&fixture("src/index.ts"), &options).unwrap();
+
+        assert_eq!(code.matches(r#"from "react""#).count(), 1);
+        assert!(!code.contains(r#""./"#));
+        assert!(code.contains("interface Options"));
+        assert!(code.contains("interface Config "));
+        assert!(code.contains("interface Config$1 "));
+        assert!(code.contains("Config$1 as ListConfig"));
+        assert!(code.contains("options as default"));
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use anyhow::{Context, Result};
+use log::debug;
+use serde_json::{Value, json};
+
+use crate::BundlessOptions;
+
+// 加载顺序(后者覆盖前者), 同 vite
+// @refer: https://vite.dev/guide/env-and-mode#env-files
+// .env
+// .env.local
+// .env.[mode]
+// .env.[mode].local
+fn env_files(mode: &str) -> [String; 4] {
+    [
+        ".env".to_string(),
+        ".env.local".to_string(),
+        format!(".env.{mode}"),
+        format!(".env.{mode}.local"),
+    ]
+}
+
+// lecp 自身读取的变量, 不暴露给产物
+const INTERNAL_VARS: [&str; 1] = ["LECP_LOG"];
+
+fn is_exposed(key: &str, prefix: &[String]) -> bool {
+    !INTERNAL_VARS.contains(&key) && prefix.iter().any(|prefix| key.starts_with(prefix.as_str()))
+}
+
+/// Load env variables from dotenv files, filtered by `env.prefix`.
+/// Variables already present in the process environment take precedence over files.
+/// lecp internal variables (`LECP_LOG`) are never exposed.
+pub fn load_env(options: &BundlessOptions) -> Result<BTreeMap<String, String>> {
+    let mut vars = BTreeMap::new();
+
+    let Some(env) = &options.env else {
+        return Ok(vars);
+    };
+
+    let env_dir = env.dir.as_ref().map_or_else(|| options.cwd.clone(), |dir| options.cwd.join(dir));
+
+    for name in env_files(&env.mode) {
+        let path = env_dir.join(&name);
+        if !path.is_file() {
+            continue;
+        }
+
+        debug!("load env file {path:?}");
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read env file {}", path.display()))?;
+        vars.extend(parse_dotenv(&content));
+    }
+
+    vars.extend(std::env::vars());
+    vars.retain(|key, _| is_exposed(key, &env.prefix));
+
+    Ok(vars)
+}
+
+/// Build the define map consumed by `swc_transform_define`
+/// - `process.env.X`
+/// - `import.meta.env.X`
+/// - `import.meta.env` (MODE, DEV, PROD and all filtered variables)
+pub fn env_to_define(options: &BundlessOptions) -> Result<Value> {
+    let Some(env) = &options.env else {
+        return Ok(json!({}));
+    };
+
+    let vars = load_env(options)?;
+    let mut define = serde_json::Map::new();
+    let mut meta_env = serde_json::Map::new();
+
+    let is_prod = env.mode == "production";
+    meta_env.insert("MODE".to_string(), json!(env.mode));
+    meta_env.insert("DEV".to_string(), json!(!is_prod));
+    meta_env.insert("PROD".to_string(), json!(is_prod));
+
+    for (key, value) in vars {
+        define.insert(format!("process.env.{key}"), json!(value));
+        meta_env.insert(key, json!(value));
+    }
+
+    for (key, value) in &meta_env {
+        define.insert(format!("import.meta.env.{key}"), value.clone());
+    }
+    define.insert("import.meta.env".to_string(), Value::Object(meta_env));
+
+    Ok(Value::Object(define))
+}
+
+/// Parse a dotenv file
+/// - `KEY=value`, `export KEY=value`
+/// - `#` comments, inline comments after unquoted values
+/// - single, double and backtick quoted values (double quotes expand `\n`)
+pub fn parse_dotenv(content: &str) -> Vec<(String, String)> {
+    let mut vars = vec![];
+    let mut lines = content.lines();
+
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+
+        let key = key.trim();
+        if key.is_empty() {
+            continue;
+        }
+
+        let mut value = value.trim().to_string();
+        let quote = value.chars().next().filter(|c| matches!(c, '"' | '\'' | '`'));
+
+        let value = match quote {
+            Some(quote) => {
+                // 多行值: KEY="line1
+                // line2"
+                while !value[1..].contains(quote) {
+                    match lines.next() {
+                        Some(next) => {
+                            value.push('\n');
+                            value.push_str(next);
+                        }
+                        None => break,
+                    }
+                }
+
+                let inner = &value[1..];
+                let inner = inner.find(quote).map_or(inner, |end| &inner[..end]);
+
+                if quote == '"' { unescape(inner) } else { inner.to_string() }
+            }
+            None => value.split(" #").next().unwrap_or_default().trim().to_string(),
+        };
+
+        vars.push((key.to_string(), value));
+    }
+
+    vars
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('t') => result.push('\t'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+    use crate::Env;
+
+    fn fixture(path: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/env").join(path)
+    }
+
+    #[test]
+    fn test_parse_dotenv() {
+        let content = r#"
+# comment
+LECP_A=1
+export LECP_B = "hello world"
+LECP_C='single # not comment'
+LECP_D=value # inline comment
+LECP_E="line1\nline2"
+LECP_F="multi
+line"
+INVALID_LINE
+"#;
+
+        assert_eq!(
+            parse_dotenv(content),
+            vec![
+                ("LECP_A".to_string(), "1".to_string()),
+                ("LECP_B".to_string(), "hello world".to_string()),
+                ("LECP_C".to_string(), "single # not comment".to_string()),
+                ("LECP_D".to_string(), "value".to_string()),
+                ("LECP_E".to_string(), "line1\nline2".to_string()),
+                ("LECP_F".to_string(), "multi\nline".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_env_files_order() {
+        assert_eq!(
+            env_files("production"),
+            [".env", ".env.local", ".env.production", ".env.production.local"]
+        );
+    }
+
+    #[test]
+    fn test_load_env() {
+        // 独立的 prefix, 不受宿主环境变量影响
+        let load = |mode: &str| {
+            let env = Env::default().mode(mode).prefix(vec!["LECP_FIXTURE_".to_string()]);
+            load_env(&BundlessOptions::default().cwd(fixture("")).env(env)).unwrap()
+        };
+
+        // .env < .env.local < .env.[mode] < .env.[mode].local
+        let vars = load("production");
+        assert_eq!(vars["LECP_FIXTURE_A"], "env");
+        assert_eq!(vars["LECP_FIXTURE_B"], "local");
+        assert_eq!(vars["LECP_FIXTURE_C"], "production");
+        assert_eq!(vars["LECP_FIXTURE_D"], "production-local");
+        // prefix 过滤
+        assert!(!vars.contains_key("VITE_X"));
+        assert!(!vars.contains_key("OTHER"));
+
+        let vars = load("development");
+        assert_eq!(vars["LECP_FIXTURE_C"], "development");
+        assert!(!vars.contains_key("LECP_FIXTURE_D"));
+    }
+
+    #[test]
+    fn test_internal_vars() {
+        let prefix = Env::default().prefix;
+
+        assert!(is_exposed("LECP_API_URL", &prefix));
+        assert!(is_exposed("VITE_API_URL", &prefix));
+        assert!(!is_exposed("LECP_LOG", &prefix));
+        assert!(!is_exposed("NODE_ENV", &prefix));
+    }
+}
@@ -0,0 +1,290 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use log::warn;
+use swc_core::common::errors::HANDLER;
+use swc_core::ecma::ast::{
+    CallExpr, ExportAll, Expr, ImportDecl, Lit, NamedExport, Pass, Program, Str,
+};
+use swc_core::ecma::visit::{VisitMut, VisitMutWith, visit_mut_pass};
+use swc_transform_extensions::specifier::Bindings;
+
+/// 构建钩子 (如 node_binding 中的 JS 回调), 在 `bundless_files` 的 worker 线程中同步调用
+///
+/// 执行顺序 (单个文件):
+/// 1. `transform`: 读取源码后, swc 转换前, 返回 None 跳过该文件
+/// 2. `resolve_id`: swc before pass 中, 先于 alias / 后缀补全
+/// 3. `post_transform`: swc 转换后
+/// 4. `render_chunk`: 写入产物前 (sourceMappingURL 之前)
+///
+/// `post_transform`, `render_chunk` 修改代码时不会更新 sourcemap (开启 sourcemap 时输出警告)
+///
+/// 任一钩子返回错误时, 该文件构建失败并中断 `bundless_files`
+pub trait BundlessHooks: Send + Sync {
+    /// 重写导入路径, None: 保持不变
+    fn resolve_id(&self, _specifier: &str, _importer: &Path) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// None: 跳过该文件, 不生成产物
+    fn transform(&self, code: String, _id: &Path) -> Result<Option<String>> {
+        Ok(Some(code))
+    }
+
+    fn post_transform(&self, code: String, _id: &Path) -> Result<String> {
+        Ok(code)
+    }
+
+    /// out_path: 产物路径
+    fn render_chunk(&self, code: String, _out_path: &Path) -> Result<String> {
+        Ok(code)
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct Hooks(Option<Arc<dyn BundlessHooks>>);
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Hooks").field(&self.0.is_some()).finish()
+    }
+}
+
+impl Hooks {
+    pub(crate) fn new(hooks: Arc<dyn BundlessHooks>) -> Self {
+        Self(Some(hooks))
+    }
+
+    pub(crate) fn get(&self) -> Option<&Arc<dyn BundlessHooks>> {
+        self.0.as_ref()
+    }
+
+    /// 读取源码并执行 `transform` 钩子, None: 跳过该文件
+    pub(crate) fn load(&self, file: &Path) -> Result<Option<Load>> {
+        let Some(hooks) = &self.0 else {
+            return Ok(Some(Load::File));
+        };
+
+        let code = std::fs::read_to_string(file)?;
+        let code = hooks
+            .transform(code, file)
+            .with_context(|| format!("transform hook failed: {}", file.display()))?;
+
+        Ok(code.map(Load::Code))
+    }
+
+    /// sourcemap: 是否生成 sourcemap, 钩子修改代码时提示 sourcemap 未更新
+    pub(crate) fn post_transform(
+        &self,
+        code: String,
+        file: &Path,
+        sourcemap: bool,
+    ) -> Result<String> {
+        match &self.0 {
+            Some(hooks) => {
+                let original = sourcemap.then(|| code.clone());
+                let code = hooks
+                    .post_transform(code, file)
+                    .with_context(|| format!("postTransform hook failed: {}", file.display()))?;
+                warn_stale_sourcemap("postTransform", original.as_deref(), &code, file);
+                Ok(code)
+            }
+            None => Ok(code),
+        }
+    }
+
+    pub(crate) fn render_chunk(
+        &self,
+        code: String,
+        out_path: &Path,
+        sourcemap: bool,
+    ) -> Result<String> {
+        match &self.0 {
+            Some(hooks) => {
+                let original = sourcemap.then(|| code.clone());
+                let code = hooks
+                    .render_chunk(code, out_path)
+                    .with_context(|| format!("renderChunk hook failed: {}", out_path.display()))?;
+                warn_stale_sourcemap("renderChunk", original.as_deref(), &code, out_path);
+                Ok(code)
+            }
+            None => Ok(code),
+        }
+    }
+}
+
+// postTransform / renderChunk 只返回代码, sourcemap 仍对应钩子执行前的代码
+fn warn_stale_sourcemap(hook: &str, original: Option<&str>, code: &str, file: &Path) {
+    if original.is_some_and(|original| original != code) {
+        warn!(
+            "{hook} hook changed {} while sourcemap is enabled, the sourcemap is not updated",
+            file.display()
+        );
+    }
+}
+
+pub(crate) enum Load {
+    /// 由 swc 读取文件
+    File,
+    Code(String),
+}
+
+/// `resolve_id` 钩子抛出的第一个错误, 由 `transform_file` 在转换后返回
+pub(crate) type ResolveIdError = Arc<Mutex<Option<anyhow::Error>>>;
+
+/// `resolve_id` 钩子: import / export from / import() / require() / require.resolve()
+pub(crate) fn resolve_id_pass(
+    hooks: Arc<dyn BundlessHooks>,
+    importer: &Path,
+    error: ResolveIdError,
+) -> impl Pass {
+    visit_mut_pass(ResolveId {
+        hooks,
+        importer: importer.to_path_buf(),
+        bindings: Default::default(),
+        error,
+    })
+}
+
+struct ResolveId {
+    hooks: Arc<dyn BundlessHooks>,
+    importer: PathBuf,
+    bindings: Bindings,
+    error: ResolveIdError,
+}
+
+impl ResolveId {
+    fn resolve(&self, src: &mut Str) {
+        let specifier = src.value.to_atom_lossy().to_string();
+
+        match self.hooks.resolve_id(&specifier, &self.importer) {
+            Ok(Some(resolved)) => *src = Str { span: src.span, ..Str::from(resolved) },
+            Ok(None) => {}
+            // 通过 handler 报错 (带源码位置), 同时记录错误, handler 未设置时也能中断构建
+            Err(e) => {
+                let msg = format!("resolveId `{specifier}`: {e}");
+                if HANDLER.is_set() {
+                    HANDLER.with(|handler| handler.struct_span_err(src.span, &msg).emit());
+                }
+                let mut error = self.error.lock().unwrap();
+                if error.is_none() {
+                    *error = Some(anyhow::anyhow!("{}: {msg}", self.importer.display()));
+                }
+            }
+        }
+    }
+}
+
+impl VisitMut for ResolveId {
+    fn visit_mut_program(&mut self, n: &mut Program) {
+        self.bindings = Bindings::new(n);
+        n.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_import_decl(&mut self, n: &mut ImportDecl) {
+        self.resolve(&mut n.src);
+    }
+
+    fn visit_mut_export_all(&mut self, n: &mut ExportAll) {
+        self.resolve(&mut n.src);
+    }
+
+    fn visit_mut_named_export(&mut self, n: &mut NamedExport) {
+        if let Some(src) = &mut n.src {
+            self.resolve(src);
+        }
+    }
+
+    // import('./foo'), require('./foo'), require.resolve('./foo'), import.meta.resolve('./foo')
+    fn visit_mut_call_expr(&mut self, n: &mut CallExpr) {
+        n.visit_mut_children_with(self);
+
+        if self.bindings.is_specifier_call(&n.callee) {
+            if let Some(arg) = n.args.first_mut() {
+                if let Expr::Lit(Lit::Str(src)) = &mut *arg.expr {
+                    self.resolve(src);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swc::transform_file;
+    use crate::{BundlessBuilder, BundlessOptions};
+
+    #[derive(Default)]
+    struct TestHooks {
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl BundlessHooks for TestHooks {
+        fn resolve_id(&self, specifier: &str, _importer: &Path) -> Result<Option<String>> {
+            self.calls.lock().unwrap().push(format!("resolveId {specifier}"));
+            Ok((specifier == "lodash").then(|| "lodash-es".to_string()))
+        }
+
+        fn transform(&self, code: String, _id: &Path) -> Result<Option<String>> {
+            self.calls.lock().unwrap().push("transform".to_string());
+            Ok(Some(code))
+        }
+
+        fn post_transform(&self, code: String, _id: &Path) -> Result<String> {
+            self.calls.lock().unwrap().push("postTransform".to_string());
+            Ok(format!("/* banner */\n{code}"))
+        }
+    }
+
+    fn fixture(path: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/hooks").join(path)
+    }
+
+    #[test]
+    fn test_hooks() {
+        let hooks = Arc::new(TestHooks::default());
+        let options = BundlessBuilder::new(BundlessOptions::default().cwd(fixture("")))
+            .hooks(hooks.clone())
+            .build();
+
+        let file = fixture("src/index.ts");
+        let Some(Load::Code(code)) = options.hooks.load(&file).unwrap() else {
+            panic!("expected code");
+        };
+
+        let swc_options = options.build_for_swc().unwrap();
+        let output = transform_file(&file, Some(code), &swc_options, &options).unwrap();
+        let code = options.hooks.post_transform(output.code, &file, false).unwrap();
+
+        assert!(code.starts_with("/* banner */"));
+        assert!(code.contains("lodash-es"));
+        assert_eq!(
+            *hooks.calls.lock().unwrap(),
+            ["transform", "resolveId lodash", "resolveId ./utils", "postTransform"]
+        );
+    }
+
+    struct FailingHooks;
+
+    impl BundlessHooks for FailingHooks {
+        fn resolve_id(&self, specifier: &str, _importer: &Path) -> Result<Option<String>> {
+            anyhow::bail!("can not resolve {specifier}")
+        }
+    }
+
+    #[test]
+    fn test_resolve_id_error() {
+        let options = BundlessBuilder::new(BundlessOptions::default().cwd(fixture("")))
+            .hooks(Arc::new(FailingHooks))
+            .build();
+
+        let swc_options = options.build_for_swc().unwrap();
+        let err = transform_file(&fixture("src/index.ts"), None, &swc_options, &options)
+            .err()
+            .expect("resolveId error should fail the build");
+        assert!(format!("{err:?}").contains("can not resolve lodash"));
+    }
+}
@@ -0,0 +1,304 @@
+use swc_core::common::DUMMY_SP;
+use swc_core::common::util::take::Take;
+use swc_core::ecma::ast::{
+    CallExpr, Callee, ComputedPropName, Decl, Expr, ExprStmt, Ident, IdentName, ImportDecl,
+    ImportSpecifier, KeyValueProp, Lit, MemberExpr, MemberProp, ModuleDecl, ModuleExportName,
+    ModuleItem, NamedExport, ObjectLit, Pass, Pat, Prop, PropName, PropOrSpread, Stmt, Str,
+    VarDecl, VarDeclKind, VarDeclarator,
+};
+use swc_core::ecma::utils::{ExprFactory, prepend_stmts, private_ident, quote_str};
+use swc_core::ecma::visit::{VisitMut, VisitMutWith, noop_visit_mut_type, visit_mut_pass};
+
+use crate::{BundlessOptions, ModuleType};
+
+/// esm 产物中 json 导入的处理方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum JsonImportMode {
+    /// import data from './data.json' with { type: 'json' }
+    Attributes,
+    /// const data = __require('./data.json')
+    Require,
+}
+
+/// - cjs 产物由 swc 转换为 require, 无需处理
+/// - 非 node targets 由打包工具处理 json 导入, 保持不变
+pub(crate) fn json_import_mode(options: &BundlessOptions) -> Option<JsonImportMode> {
+    if !matches!(options.format, ModuleType::ESM) || !options.is_node() {
+        return None;
+    }
+
+    match options.node_version() {
+        Some(version) if !supports_import_attributes(version) => Some(JsonImportMode::Require),
+        _ => Some(JsonImportMode::Attributes),
+    }
+}
+
+// with { type: 'json' }: node 18.20+, 20.10+
+fn supports_import_attributes((major, minor): (u64, u64)) -> bool {
+    major >= 21 || (major == 20 && minor >= 10) || (major == 18 && minor >= 20)
+}
+
+/// json 导入: 添加 import attributes 或转换为 createRequire
+///
+/// json / wasm 文件由 packages/core 随其他静态资源复制到产物目录
+pub(crate) fn transform(mode: Option<JsonImportMode>) -> impl Pass {
+    visit_mut_pass(JsonImports { mode, require_ident: None })
+}
+
+fn is_json(src: &Str) -> bool {
+    src.value.to_atom_lossy().ends_with(".json")
+}
+
+// { type: "json" }
+fn json_attributes() -> Box<ObjectLit> {
+    Box::new(ObjectLit {
+        span: DUMMY_SP,
+        props: vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: PropName::Ident(IdentName::new("type".into(), DUMMY_SP)),
+            value: Box::new(Expr::Lit(Lit::Str(quote_str!("json")))),
+        })))],
+    })
+}
+
+struct JsonImports {
+    mode: Option<JsonImportMode>,
+    require_ident: Option<Ident>,
+}
+
+impl JsonImports {
+    /// import data, { version } from './package.json'
+    /// -> const data = __require('./package.json'), version = __require('./package.json').version
+    fn require_import(&mut self, decl: ImportDecl) -> ModuleItem {
+        let require = self.require_ident.get_or_insert_with(|| private_ident!("__require")).clone();
+        let call = require.as_call(DUMMY_SP, vec![Expr::Lit(Lit::Str(*decl.src)).as_arg()]);
+
+        // import './data.json'
+        if decl.specifiers.is_empty() {
+            return ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+                span: decl.span,
+                expr: Box::new(call),
+            }));
+        }
+
+        let decls = decl
+            .specifiers
+            .into_iter()
+            .map(|specifier| {
+                let (local, init) = match specifier {
+                    ImportSpecifier::Default(specifier) => (specifier.local, call.clone()),
+                    // import * as data -> { default: data }
+                    ImportSpecifier::Namespace(specifier) => {
+                        let init = Expr::Object(ObjectLit {
+                            span: DUMMY_SP,
+                            props: vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(
+                                KeyValueProp {
+                                    key: PropName::Ident(IdentName::new(
+                                        "default".into(),
+                                        DUMMY_SP,
+                                    )),
+                                    value: Box::new(call.clone()),
+                                },
+                            )))],
+                        });
+                        (specifier.local, init)
+                    }
+                    ImportSpecifier::Named(specifier) => {
+                        let prop = match &specifier.imported {
+                            Some(ModuleExportName::Ident(imported)) => {
+                                MemberProp::Ident(imported.clone().into())
+                            }
+                            Some(ModuleExportName::Str(imported)) => {
+                                MemberProp::Computed(ComputedPropName {
+                                    span: DUMMY_SP,
+                                    expr: Box::new(Expr::Lit(Lit::Str(imported.clone()))),
+                                })
+                            }
+                            None => MemberProp::Ident(specifier.local.clone().into()),
+                        };
+
+                        let init = match &prop {
+                            MemberProp::Ident(prop) if prop.sym.as_ref() == "default" => {
+                                call.clone()
+                            }
+                            _ => Expr::Member(MemberExpr {
+                                span: DUMMY_SP,
+                                obj: Box::new(call.clone()),
+                                prop,
+                            }),
+                        };
+                        (specifier.local, init)
+                    }
+                };
+
+                VarDeclarator {
+                    span: DUMMY_SP,
+                    name: Pat::Ident(local.into()),
+                    init: Some(Box::new(init)),
+                    definite: false,
+                }
+            })
+            .collect();
+
+        ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+            span: decl.span,
+            kind: VarDeclKind::Const,
+            declare: false,
+            decls,
+            ctxt: Default::default(),
+        }))))
+    }
+}
+
+impl VisitMut for JsonImports {
+    noop_visit_mut_type!();
+
+    fn visit_mut_named_export(&mut self, n: &mut NamedExport) {
+        if let Some(src) = &n.src {
+            // export { default as data } from './data.json'
+            if is_json(src) && n.with.is_none() && self.mode.is_some() {
+                n.with = Some(json_attributes());
+            }
+        }
+    }
+
+    // import('./data.json') -> import('./data.json', { with: { type: 'json' } })
+    fn visit_mut_call_expr(&mut self, n: &mut CallExpr) {
+        n.visit_mut_children_with(self);
+
+        if !matches!(n.callee, Callee::Import(_)) {
+            return;
+        }
+
+        let Some(Expr::Lit(Lit::Str(src))) = n.args.first().map(|arg| &*arg.expr) else {
+            return;
+        };
+
+        if is_json(src) && n.args.len() == 1 {
+            if let Some(JsonImportMode::Attributes) = self.mode {
+                let options = ObjectLit {
+                    span: DUMMY_SP,
+                    props: vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                        key: PropName::Ident(IdentName::new("with".into(), DUMMY_SP)),
+                        value: Box::new(Expr::Object(*json_attributes())),
+                    })))],
+                };
+                n.args.push(options.as_arg());
+            }
+        }
+    }
+
+    fn visit_mut_module_items(&mut self, items: &mut Vec<ModuleItem>) {
+        items.visit_mut_children_with(self);
+
+        let Some(mode) = self.mode else {
+            return;
+        };
+
+        *items = items
+            .take()
+            .into_iter()
+            .map(|item| match item {
+                ModuleItem::ModuleDecl(ModuleDecl::Import(mut decl))
+                    if is_json(&decl.src) && !decl.type_only && decl.with.is_none() =>
+                {
+                    // json 模块只有 default 导出, 具名导入需转换为 require
+                    let has_named = decl
+                        .specifiers
+                        .iter()
+                        .any(|specifier| matches!(specifier, ImportSpecifier::Named(_)));
+
+                    if mode == JsonImportMode::Require || has_named {
+                        self.require_import(decl)
+                    } else {
+                        decl.with = Some(json_attributes());
+                        ModuleItem::ModuleDecl(ModuleDecl::Import(decl))
+                    }
+                }
+                item => item,
+            })
+            .collect();
+
+        // import { createRequire as _createRequire } from "node:module";
+        // const __require = _createRequire(import.meta.url);
+        // 与 shims 共用, shims 的 require -> __require 复用该声明
+        if let Some(require) = &self.require_ident {
+            // 插入到 "use client" 等指令之后
+            prepend_stmts(items, swc_transform_shims::create_require(require.clone()).into_iter());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+    use crate::Shims;
+    use crate::swc::transform_file;
+
+    fn fixture(path: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/json").join(path)
+    }
+
+    fn esm_options(targets: serde_json::Value) -> BundlessOptions {
+        BundlessOptions {
+            out_dir: Some(std::env::temp_dir().join("lecp-bundless-json")),
+            ..BundlessOptions::default()
+                .cwd(fixture(""))
+                .format(ModuleType::ESM)
+                .targets(targets)
+                .shims(Shims::Boolean(true))
+        }
+    }
+
+    fn transform_code(file: &Path, options: &BundlessOptions) -> String {
+        let swc_options = options.build_for_swc().unwrap();
+        transform_file(file, None, &swc_options, options).unwrap().code
+    }
+
+    #[test]
+    fn test_json_import_mode() {
+        let mode = |targets: serde_json::Value| {
+            json_import_mode(&BundlessOptions::default().format(ModuleType::ESM).targets(targets))
+        };
+
+        assert_eq!(mode(serde_json::json!({ "chrome": "100" })), None);
+        assert_eq!(mode(serde_json::json!({ "node": "20.10" })), Some(JsonImportMode::Attributes));
+        assert_eq!(
+            mode(serde_json::json!({ "node": "18.20.0" })),
+            Some(JsonImportMode::Attributes)
+        );
+        assert_eq!(mode(serde_json::json!({ "node": "20.9" })), Some(JsonImportMode::Require));
+        assert_eq!(mode(serde_json::json!({ "node": "16" })), Some(JsonImportMode::Require));
+
+        let cjs = BundlessOptions::default().format(ModuleType::CJS);
+        assert_eq!(json_import_mode(&cjs), None);
+    }
+
+    #[test]
+    fn test_json_require() {
+        let options = esm_options(serde_json::json!({ "node": "16" }));
+        let code = transform_code(&fixture("src/index.ts"), &options);
+
+        assert!(code.contains("__require(\"./data.json\")"));
+        assert!(code.contains("__require(\"./data.json\").version"));
+        assert!(code.contains("__require(\"fs\")"));
+        assert!(!code.contains("with {"));
+        // json 与 shims 共用同一个 createRequire
+        assert_eq!(code.matches("\"node:module\"").count(), 1);
+        assert_eq!(code.matches("import.meta.url").count(), 1);
+    }
+
+    #[test]
+    fn test_json_attributes() {
+        let file = fixture("src/attributes.ts");
+
+        let code = transform_code(&file, &esm_options(serde_json::json!({ "node": "22" })));
+        assert!(code.contains("type: \"json\""));
+        assert!(!code.contains("node:module"));
+
+        // 非 node targets 保持不变
+        let code = transform_code(&file, &esm_options(serde_json::json!({ "chrome": "100" })));
+        assert!(!code.contains("type: \"json\""));
+    }
+}
@@ -1,6 +1,18 @@
+mod banner;
+mod builder;
+mod define;
+mod dts;
+mod dts_bundle;
+mod env;
+mod hooks;
+mod json;
 mod options;
+mod plugins;
+mod shims;
 mod swc;
+mod tsconfig;
 mod util;
+mod verify;
 use std::path::Path;
 
 use anyhow::Result;
@@ -10,9 +22,23 @@ use rayon::prelude::*;
 use swc::{transform_file, write_file_and_sourcemap};
 use wax::Glob;
 
-pub use crate::options::{BundlessOptions, CSS, Define, JsxRuntime, ModuleType, React, Shims};
+use crate::banner::add_banner_footer;
+pub use crate::builder::{AFTER_PASSES, BEFORE_PASSES, BundlessBuilder, PassFactory, PassPhase};
+use crate::define::{report_define_conflicts, report_unused_define};
+pub use crate::dts::{DtsOutput, transform_dts};
+use crate::dts::{fix_dts_source_map, report_dts_diagnostics};
+pub use crate::dts_bundle::bundle_dts;
+pub use crate::env::load_env;
+pub use crate::hooks::BundlessHooks;
+use crate::hooks::Load;
+pub use crate::options::{
+    BundlessOptions, CSS, Define, Dts, Env, JsxRuntime, ModuleType, Plugin, Polyfill, React, Shims,
+};
+use crate::shims::report_shims_usage;
+pub use crate::tsconfig::{TsConfig, load_tsconfig};
 pub use crate::util::serde_error_to_miette;
 use crate::util::write_file;
+use crate::verify::verify_outputs;
 
 /**
  * |     | module | commonjs |
@@ -65,6 +91,27 @@ pub fn get_out_file_path<P1: AsRef<Path>, P2: AsRef<Path>, P3: AsRef<Path>>(
     Ok(out_path)
 }
 
+// 与 js 产物配对, 按产物后缀确定 (TypeScript 按 js 产物查找声明文件)
+// - .mjs, .cjs -> .d.mts, .d.cts
+// - .js -> .d.ts (含 .mts, .cts 源文件输出为 .js 的情况)
+// out_path: /demo/dist/utils/index.mjs -> /demo/dist/utils/index.d.mts
+pub fn get_out_dts_file_path<P1: AsRef<Path>, P2: AsRef<Path>, P3: AsRef<Path>>(
+    path: P1,
+    src_dir: P2,
+    out_dir: P3,
+    out_ext: &str,
+) -> Result<std::path::PathBuf> {
+    let out_path = get_out_file_path(path, src_dir, out_dir, out_ext)?;
+
+    let dts_ext = match out_path.extension().and_then(|ext| ext.to_str()) {
+        Some("mjs") => "d.mts",
+        Some("cjs") => "d.cts",
+        _ => "d.ts",
+    };
+
+    Ok(out_path.with_extension(dts_ext))
+}
+
 use std::io::Write;
 use std::sync::Once;
 
@@ -89,6 +136,8 @@ fn init_logger() {
 
 pub fn bundless_files(options: &BundlessOptions) -> Result<()> {
     init_logger();
+    // 新的缓存 (tsconfig, env, alias, plugins), watch 模式下文件可能已修改
+    let options = &options.clone();
     // let cwd = &options.cwd;
     // println!("Bundless CLI: {:?}", cwd);
 
@@ -96,6 +145,10 @@ pub fn bundless_files(options: &BundlessOptions) -> Result<()> {
 
     debug!("bundless options: {:#?}", &options);
 
+    report_define_conflicts(options);
+    *options.define_stats.lock().unwrap() = Default::default();
+    options.shims_stats.lock().unwrap().clear();
+
     // 测试相关文件(glob格式)
     // wax crate 不支持某些高级的 glob 语法，特别是 {,/**} 这种大括号扩展和 **/*.+(test|e2e|spec).* 这种扩展模式。
     let test_pattern = vec![
@@ -131,24 +184,50 @@ pub fn bundless_files(options: &BundlessOptions) -> Result<()> {
     // );
 
     let glob: Glob<'_> = Glob::new("**/*.{ts,tsx,cts,mts,js,jsx,cjs,mjs}")?;
-    glob.walk(&src_dir)
+    let outputs = glob
+        .walk(&src_dir)
         .not(ignore)?
         .par_bridge()
         .filter_map(Result::ok)
-        .map(|entry| entry.path().to_owned())
-        .try_for_each(|path| bundless_file(path, options))?;
+        .map(|entry| build_file(entry.path(), options))
+        .collect::<Result<Vec<_>>>()?;
+    let outputs = outputs.into_iter().flatten().collect::<Vec<_>>();
+
+    report_unused_define(options);
+    report_shims_usage(options);
+
+    // ./a.js -> ./a.mjs 后检查文件确实生成 (仅本次构建的产物)
+    verify_outputs(options, &outputs, false)?;
 
     Ok(())
 }
 
 pub fn bundless_file<P: AsRef<Path>>(file: P, options: &BundlessOptions) -> Result<()> {
-    let file = file.as_ref();
+    build_file(file.as_ref(), options).map(|_| ())
+}
+
+/// 编译单个文件, 返回产物路径 (被 load 钩子跳过时为 None)
+fn build_file(file: &Path, options: &BundlessOptions) -> Result<Option<std::path::PathBuf>> {
     let cwd = &options.cwd;
 
     if !file.exists() {
         return Err(anyhow::anyhow!("File does not exist: {:?}", file));
     }
 
+    // transform 钩子
+    let source = match options.hooks.load(file)? {
+        Some(Load::File) => None,
+        Some(Load::Code(code)) => Some(code),
+        None => {
+            info!(
+                "bundless({}) {} skipped by hooks",
+                options.format.get_type(),
+                &file.strip_prefix(cwd)?.display().yellow()
+            );
+            return Ok(None);
+        }
+    };
+
     let mut swc_options = options.build_for_swc()?;
 
     let src_dir = options.src_dir();
@@ -159,7 +238,10 @@ pub fn bundless_file<P: AsRef<Path>>(file: P, options: &BundlessOptions) -> Resu
 
     swc_options.output_path = Some(out_path.to_owned());
 
-    let output = transform_file(file, &swc_options, options)?;
+    let mut output = transform_file(file, source, &swc_options, options)?;
+    output.code = options.hooks.post_transform(output.code, file, options.sourcemap)?;
+    add_banner_footer(&mut output.code, &mut output.map, file, options)?;
+    output.code = options.hooks.render_chunk(output.code, &out_path, options.sourcemap)?;
 
     info!(
         "bundless({}) {} to {}",
@@ -170,11 +252,13 @@ pub fn bundless_file<P: AsRef<Path>>(file: P, options: &BundlessOptions) -> Resu
 
     write_file_and_sourcemap(output, &out_path)?;
 
-    Ok(())
+    Ok(Some(out_path))
 }
 
 pub fn bundless_dts(options: &BundlessOptions) -> Result<()> {
     init_logger();
+    // 新的缓存 (tsconfig, alias), watch 模式下文件可能已修改
+    let options = &options.clone();
 
     let src_dir = options.src_dir();
 
@@ -208,18 +292,25 @@ pub fn bundless_dts(options: &BundlessOptions) -> Result<()> {
     debug!("ignore: {ignore:?}");
 
     let glob: Glob<'_> = Glob::new("**/*.{ts,tsx,cts,mts,js,jsx,cjs,mjs}")?;
-    glob.walk(&src_dir)
+    let outputs = glob
+        .walk(&src_dir)
         .not(ignore)?
         .par_bridge()
         .filter_map(Result::ok)
-        .map(|entry| entry.path().to_owned())
-        .try_for_each(|path| bundless_dts_file(path, options))?;
+        .map(|entry| build_dts_file(entry.path(), options))
+        .collect::<Result<Vec<_>>>()?;
+
+    verify_outputs(options, &outputs, true)?;
 
     Ok(())
 }
 
 pub fn bundless_dts_file<P: AsRef<Path>>(file: P, options: &BundlessOptions) -> Result<()> {
-    let file = file.as_ref();
+    build_dts_file(file.as_ref(), options).map(|_| ())
+}
+
+/// 生成单个文件的声明, 返回 .d.ts 路径
+fn build_dts_file(file: &Path, options: &BundlessOptions) -> Result<std::path::PathBuf> {
     let cwd = &options.cwd;
 
     if !file.exists() {
@@ -230,9 +321,7 @@ pub fn bundless_dts_file<P: AsRef<Path>>(file: P, options: &BundlessOptions) ->
     let out_dir = options.out_dir();
     let out_ext = options.out_ext();
 
-    let out_path = get_out_file_path(file, &src_dir, &out_dir, &out_ext)?;
-
-    let dts_file_path = out_path.with_extension("d.ts");
+    let dts_file_path = get_out_dts_file_path(file, &src_dir, &out_dir, &out_ext)?;
 
     info!(
         "bundless(dts) {} to {}",
@@ -240,10 +329,28 @@ pub fn bundless_dts_file<P: AsRef<Path>>(file: P, options: &BundlessOptions) ->
         &dts_file_path.strip_prefix(cwd)?.display().bright_black()
     );
 
-    let dts_code = transform_dts_file(file, options)?;
-    write_file(&dts_file_path, dts_code)?;
+    let mut output = transform_dts(file, options)?;
+    report_dts_diagnostics(&mut output, options)?;
 
-    Ok(())
+    let DtsOutput { mut code, map, .. } = output;
+
+    // d.ts.map
+    if let Some(map) = map {
+        let map_path = dts_file_path.with_extension(format!(
+            "{}.map",
+            dts_file_path.extension().unwrap_or_default().to_string_lossy()
+        ));
+        code.push_str(&format!(
+            "\n//# sourceMappingURL={}",
+            &map_path.file_name().unwrap().to_string_lossy()
+        ));
+
+        write_file(&map_path, fix_dts_source_map(&map, &dts_file_path)?)?;
+    }
+
+    write_file(&dts_file_path, code)?;
+
+    Ok(dts_file_path)
 }
 
 pub fn transform_dts_file<P: AsRef<Path>>(file: P, options: &BundlessOptions) -> Result<String> {
@@ -253,20 +360,82 @@ pub fn transform_dts_file<P: AsRef<Path>>(file: P, options: &BundlessOptions) ->
         return Err(anyhow::anyhow!("File does not exist: {:?}", file));
     }
 
-    let mut swc_options = options.build_for_swc()?;
+    let mut output = transform_dts(file, options)?;
+    report_dts_diagnostics(&mut output, options)?;
 
-    swc_options.config.jsc.experimental.emit_isolated_dts = true.into();
+    Ok(output.code)
+}
 
-    let output = transform_file(file, &swc_options, options)?;
+// 每个入口输出一个 .d.ts
+// entry: /demo/src/index.ts -> /demo/dist/index.d.ts
+pub fn bundless_bundle_dts<P: AsRef<Path> + Sync>(
+    entries: &[P],
+    options: &BundlessOptions,
+) -> Result<()> {
+    init_logger();
+    // 新的缓存 (tsconfig, alias), watch 模式下文件可能已修改
+    let options = &options.clone();
 
-    if let Some(extra) = &output.output {
-        let mut extra: serde_json::Map<String, serde_json::Value> = serde_json::from_str(extra)?;
+    let cwd = &options.cwd;
+    let src_dir = options.src_dir();
+    let out_dir = options.out_dir();
+    let out_ext = options.out_ext();
+
+    entries.par_iter().try_for_each(|entry| {
+        let entry = entry.as_ref();
 
-        if let Some(dts_code) = extra.remove("__swc_isolated_declarations__") {
-            let dts_code = dts_code.as_str().expect("dts code should be string");
-            return Ok(dts_code.into());
+        if !entry.exists() {
+            return Err(anyhow::anyhow!("File does not exist: {:?}", entry));
         }
+
+        let dts_file_path = get_out_dts_file_path(entry, &src_dir, &out_dir, &out_ext)?;
+
+        info!(
+            "bundle(dts) {} to {}",
+            &entry.strip_prefix(cwd)?.display().yellow(),
+            &dts_file_path.strip_prefix(cwd)?.display().bright_black()
+        );
+
+        write_file(&dts_file_path, bundle_dts(entry, options)?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_out_dts_file_path() {
+        let dts_path = |file: &str, out_ext: &str| {
+            get_out_dts_file_path(file, "/demo/src", "/demo/dist", out_ext)
+                .unwrap()
+                .to_string_lossy()
+                .to_string()
+        };
+
+        assert_eq!(dts_path("/demo/src/index.ts", "js"), "/demo/dist/index.d.ts");
+        assert_eq!(dts_path("/demo/src/index.ts", "mjs"), "/demo/dist/index.d.mts");
+        assert_eq!(dts_path("/demo/src/index.ts", "cjs"), "/demo/dist/index.d.cts");
+        assert_eq!(dts_path("/demo/src/button.tsx", "mjs"), "/demo/dist/button.d.ts");
+        assert_eq!(dts_path("/demo/src/index.mts", "mjs"), "/demo/dist/index.d.mts");
+        assert_eq!(dts_path("/demo/src/index.cts", "cjs"), "/demo/dist/index.d.cts");
     }
 
-    Ok(Default::default())
+    #[test]
+    fn test_mts_out_file_pair() {
+        // .mts 源文件输出为 .js 时, 声明文件为 .d.ts, 与 js 产物配对
+        let js_path = get_out_file_path("/demo/src/index.mts", "/demo/src", "/demo/dist", "js");
+        let dts_path =
+            get_out_dts_file_path("/demo/src/index.mts", "/demo/src", "/demo/dist", "js");
+
+        assert_eq!(js_path.unwrap(), Path::new("/demo/dist/index.js"));
+        assert_eq!(dts_path.unwrap(), Path::new("/demo/dist/index.d.ts"));
+
+        let js_path = get_out_file_path("/demo/src/index.cts", "/demo/src", "/demo/dist", "cjs");
+        let dts_path =
+            get_out_dts_file_path("/demo/src/index.cts", "/demo/src", "/demo/dist", "cjs");
+
+        assert_eq!(js_path.unwrap(), Path::new("/demo/dist/index.cjs"));
+        assert_eq!(dts_path.unwrap(), Path::new("/demo/dist/index.d.cts"));
+    }
 }
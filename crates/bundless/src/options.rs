@@ -1,13 +1,23 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Display;
 use std::path::{Path, PathBuf};
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock, Mutex, OnceLock};
 
 use anyhow::Result;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::json;
 use swc_core::base::config::Options as SwcOptions;
-
+use swc_transform_define::DefineStats;
+use swc_transform_extensions::AliasRule;
+pub use swc_transform_shims::Polyfill;
+use swc_transform_shims::ShimsUsage;
+
+use crate::builder::PassRegistry;
+use crate::env::env_to_define;
+use crate::hooks::Hooks;
+pub use crate::plugins::Plugin;
+use crate::plugins::{ResolvedPlugins, resolve_plugins_config};
+use crate::tsconfig::{TsConfig, load_tsconfig};
 use crate::util::merge_json_values;
 use crate::{get_out_ext, serde_error_to_miette};
 
@@ -18,6 +28,18 @@ pub enum Shims {
     Object {
         #[serde(default)]
         legacy: Option<bool>,
+        /// ESM 产物中 module.exports / exports.xxx -> export default / export { xxx }
+        #[serde(default)]
+        interop: bool,
+        /// CJS 产物中 export default -> module.exports = default
+        #[serde(default, rename = "cjsInterop")]
+        cjs_interop: bool,
+        /// CJS 产物中 top-level await 包裹为 async IIFE
+        #[serde(default, rename = "topLevelAwait")]
+        top_level_await: bool,
+        /// 浏览器 targets 下 process / Buffer 的 polyfill 包
+        #[serde(default)]
+        polyfill: Polyfill,
     },
 }
 
@@ -35,15 +57,44 @@ impl Shims {
         }
     }
 
-    pub fn legacy(&self) -> bool {
+    /// None: 未指定, 根据 targets.node 自动选择
+    pub fn legacy(&self) -> Option<bool> {
+        match self {
+            Shims::Boolean(_) => None,
+            Shims::Object { legacy, .. } => *legacy,
+        }
+    }
+
+    pub fn interop(&self) -> bool {
+        match self {
+            Shims::Boolean(_) => false,
+            Shims::Object { interop, .. } => *interop,
+        }
+    }
+
+    pub fn cjs_interop(&self) -> bool {
         match self {
             Shims::Boolean(_) => false,
-            Shims::Object { legacy } => legacy.unwrap_or(false),
+            Shims::Object { cjs_interop, .. } => *cjs_interop,
+        }
+    }
+
+    pub fn top_level_await(&self) -> bool {
+        match self {
+            Shims::Boolean(_) => false,
+            Shims::Object { top_level_await, .. } => *top_level_await,
+        }
+    }
+
+    pub fn polyfill(&self) -> Polyfill {
+        match self {
+            Shims::Boolean(_) => Polyfill::default(),
+            Shims::Object { polyfill, .. } => polyfill.clone(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub enum JsxRuntime {
     #[default]
@@ -52,22 +103,17 @@ pub enum JsxRuntime {
     Preserve,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// 未设置时取 tsconfig `jsx`, `jsxImportSource`, 否则 automatic, react
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct React {
-    #[serde(default = "default_jsx_runtime")]
+    #[serde(default)]
     pub runtime: Option<JsxRuntime>,
 
-    #[serde(default = "default_import_source")]
+    #[serde(default)]
     pub import_source: Option<String>,
 }
 
-impl Default for React {
-    fn default() -> Self {
-        Self { runtime: Some(JsxRuntime::default()), import_source: Some("react".to_string()) }
-    }
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum ModuleType {
@@ -145,6 +191,70 @@ pub struct Define {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", default)]
+pub struct Env {
+    /// `.env.[mode]`, `import.meta.env.MODE`
+    pub mode: String,
+    /// Only variables starting with one of the prefixes are exposed
+    pub prefix: Vec<String>,
+    /// Directory to load env files from (relative to cwd), defaults to cwd
+    pub dir: Option<PathBuf>,
+}
+
+impl Default for Env {
+    fn default() -> Self {
+        Self {
+            mode: "production".to_string(),
+            prefix: vec!["LECP_".to_string(), "VITE_".to_string()],
+            dir: None,
+        }
+    }
+}
+
+impl Env {
+    pub fn mode(mut self, mode: &str) -> Self {
+        self.mode = mode.to_string();
+        self
+    }
+
+    pub fn prefix(mut self, prefix: Vec<String>) -> Self {
+        self.prefix = prefix;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Dts {
+    /// isolated declarations 诊断视为错误 (默认仅 warn)
+    pub strict: bool,
+}
+
+impl Dts {
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+}
+
+// packages/core 中 dts 为 `boolean | {...}`, true / false 均为默认配置 (非 strict)
+fn deserialize_dts<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Dts, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum DtsOrBool {
+        Bool(bool),
+        Dts(Dts),
+    }
+
+    Ok(match DtsOrBool::deserialize(deserializer)? {
+        DtsOrBool::Bool(_) => Dts::default(),
+        DtsOrBool::Dts(dts) => dts,
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct BundlessOptions {
     #[serde(default = "default_cwd")]
     pub cwd: PathBuf,
@@ -154,17 +264,54 @@ pub struct BundlessOptions {
     pub targets: serde_json::Value,
     pub shims: Shims,
     pub external_helpers: Option<bool>,
-    #[serde(default = "default_alias")]
+    /// 未设置时: tsconfig `compilerOptions.paths` + `{"@": "./src"}`
     pub alias: Option<Alias>,
     pub define: Option<Define>,
+    pub env: Option<Env>,
     pub css: Option<CSS>,
+    #[serde(deserialize_with = "deserialize_dts")]
+    pub dts: Dts,
     pub react: React,
     pub out_ext: String,
     pub exclude: Vec<String>,
     pub swc_options: Option<serde_json::Value>,
+    /// swc Wasm plugins: `[name, config]`, name 为 npm 包名或 .wasm 路径
+    pub plugins: Vec<Plugin>,
+    /// 产物头部内容 (如 license 注释, "use client"), 支持 `[name]`, `[path]`
+    pub banner: Option<String>,
+    /// 产物尾部内容, 支持 `[name]`, `[path]`
+    pub footer: Option<String>,
     pub out_dir: Option<PathBuf>,
     pub src_dir: Option<PathBuf>,
     pub is_module: bool,
+    /// tsconfig 路径 (相对 cwd), 默认 `tsconfig.json`
+    pub tsconfig: Option<PathBuf>,
+    /// define 替换统计 (跨文件汇总)
+    #[serde(skip)]
+    pub(crate) define_stats: Arc<Mutex<DefineStats>>,
+    /// shims 统计 (文件 -> 使用的 shims)
+    #[serde(skip)]
+    pub(crate) shims_stats: Arc<Mutex<BTreeMap<PathBuf, ShimsUsage>>>,
+    /// 自定义 swc passes, 见 [crate::BundlessBuilder]
+    #[serde(skip)]
+    pub(crate) passes: PassRegistry,
+    /// 构建钩子, 见 [crate::BundlessHooks]
+    #[serde(skip)]
+    pub(crate) hooks: Hooks,
+    /// tsconfig, 每次构建只加载一次
+    ///
+    /// 缓存 (tsconfig, env, alias, plugins) 不随 clone 共享, 见 [BundlessOptions::clone]
+    #[serde(skip)]
+    pub(crate) tsconfig_cache: Arc<OnceLock<Option<TsConfig>>>,
+    /// env 变量 -> define (.env 文件), 每次构建只加载一次
+    #[serde(skip)]
+    pub(crate) env_cache: Arc<OnceLock<serde_json::Value>>,
+    /// alias 规则, 每次构建只计算一次
+    #[serde(skip)]
+    pub(crate) alias_cache: Arc<OnceLock<Vec<AliasRule>>>,
+    /// swc Wasm plugins, 每次构建只解析一次
+    #[serde(skip)]
+    pub(crate) plugins_cache: Arc<OnceLock<ResolvedPlugins>>,
 }
 
 fn default_cwd() -> PathBuf {
@@ -172,18 +319,6 @@ fn default_cwd() -> PathBuf {
     CWD.clone()
 }
 
-fn default_alias() -> Option<Alias> {
-    Some(Alias::default())
-}
-
-fn default_jsx_runtime() -> Option<JsxRuntime> {
-    Some(JsxRuntime::default())
-}
-
-fn default_import_source() -> Option<String> {
-    Some("react".to_string())
-}
-
 impl Default for BundlessOptions {
     fn default() -> Self {
         let cwd = default_cwd();
@@ -193,18 +328,72 @@ impl Default for BundlessOptions {
             sourcemap: Default::default(),
             minify: Default::default(),
             targets: Default::default(),
-            alias: Some(Default::default()),
+            alias: Default::default(),
             shims: Default::default(),
             define: Default::default(),
+            env: Default::default(),
             external_helpers: Default::default(),
             css: Default::default(),
+            dts: Default::default(),
             react: Default::default(),
             exclude: vec![],
             swc_options: Default::default(),
+            plugins: vec![],
+            banner: Default::default(),
+            footer: Default::default(),
             out_dir: Default::default(),
             out_ext: Default::default(),
             src_dir: Default::default(),
             is_module: Default::default(),
+            tsconfig: Default::default(),
+            define_stats: Default::default(),
+            shims_stats: Default::default(),
+            passes: Default::default(),
+            hooks: Default::default(),
+            tsconfig_cache: Default::default(),
+            env_cache: Default::default(),
+            alias_cache: Default::default(),
+            plugins_cache: Default::default(),
+        }
+    }
+}
+
+/// 统计 (define, shims), passes, hooks 与原 options 共享;
+/// 缓存 (tsconfig, env, alias, plugins) 重新计算, clone 后可修改 cwd 等选项
+impl Clone for BundlessOptions {
+    fn clone(&self) -> Self {
+        Self {
+            cwd: self.cwd.clone(),
+            format: self.format.clone(),
+            sourcemap: self.sourcemap,
+            minify: self.minify,
+            targets: self.targets.clone(),
+            alias: self.alias.clone(),
+            shims: self.shims.clone(),
+            define: self.define.clone(),
+            env: self.env.clone(),
+            external_helpers: self.external_helpers,
+            css: self.css.clone(),
+            dts: self.dts.clone(),
+            react: self.react.clone(),
+            exclude: self.exclude.clone(),
+            swc_options: self.swc_options.clone(),
+            plugins: self.plugins.clone(),
+            banner: self.banner.clone(),
+            footer: self.footer.clone(),
+            out_dir: self.out_dir.clone(),
+            out_ext: self.out_ext.clone(),
+            src_dir: self.src_dir.clone(),
+            is_module: self.is_module,
+            tsconfig: self.tsconfig.clone(),
+            define_stats: self.define_stats.clone(),
+            shims_stats: self.shims_stats.clone(),
+            passes: self.passes.clone(),
+            hooks: self.hooks.clone(),
+            tsconfig_cache: Default::default(),
+            env_cache: Default::default(),
+            alias_cache: Default::default(),
+            plugins_cache: Default::default(),
         }
     }
 }
@@ -230,6 +419,11 @@ impl BundlessOptions {
         self
     }
 
+    pub fn env(mut self, env: Env) -> Self {
+        self.env = Some(env);
+        self
+    }
+
     pub fn external_helpers(mut self, external_helpers: bool) -> Self {
         self.external_helpers = Some(external_helpers);
         self
@@ -245,6 +439,11 @@ impl BundlessOptions {
         self
     }
 
+    pub fn dts(mut self, dts: Dts) -> Self {
+        self.dts = dts;
+        self
+    }
+
     pub fn react(mut self, react: React) -> Self {
         self.react = react;
         self
@@ -265,11 +464,31 @@ impl BundlessOptions {
         self
     }
 
+    pub fn plugins(mut self, plugins: Vec<Plugin>) -> Self {
+        self.plugins = plugins;
+        self
+    }
+
+    pub fn banner<S: Into<String>>(mut self, banner: S) -> Self {
+        self.banner = Some(banner.into());
+        self
+    }
+
+    pub fn footer<S: Into<String>>(mut self, footer: S) -> Self {
+        self.footer = Some(footer.into());
+        self
+    }
+
     pub fn is_module(mut self, is_module: bool) -> Self {
         self.is_module = is_module;
         self
     }
 
+    pub fn tsconfig<P: AsRef<Path>>(mut self, tsconfig: P) -> Self {
+        self.tsconfig = Some(tsconfig.as_ref().to_path_buf());
+        self
+    }
+
     pub fn out_dir(&self) -> PathBuf {
         let out = match &self.format {
             ModuleType::ESM => "es",
@@ -305,6 +524,33 @@ impl BundlessOptions {
             None
         };
 
+        // tsconfig: jsx, experimentalDecorators, emitDecoratorMetadata
+        // 无 tsconfig 时保持 legacy decorators + metadata
+        let tsconfig = self.resolved_tsconfig()?;
+        let plugins = self.resolved_plugins()?;
+
+        let jsx_runtime = self
+            .react
+            .runtime
+            .clone()
+            .or_else(|| tsconfig.and_then(TsConfig::jsx_runtime))
+            .unwrap_or_default();
+        let jsx_import_source = self
+            .react
+            .import_source
+            .clone()
+            .or_else(|| tsconfig.and_then(|tsconfig| tsconfig.jsx_import_source.clone()))
+            .unwrap_or_else(|| "react".to_string());
+
+        let (legacy_decorator, decorator_metadata) = match tsconfig {
+            Some(tsconfig) => {
+                let legacy = tsconfig.experimental_decorators.unwrap_or(false);
+                (legacy, legacy && tsconfig.emit_decorator_metadata.unwrap_or(false))
+            }
+            None => (true, true),
+        };
+        let decorator_version = if legacy_decorator { "2021-12" } else { "2022-03" };
+
         // default
         let mut config_json = json!({
             "env": {
@@ -320,24 +566,24 @@ impl BundlessOptions {
                     "decorators": true
                 },
                 "transform": {
-                    "legacyDecorator": true,
-                    "decoratorMetadata": true,
+                    "legacyDecorator": legacy_decorator,
+                    "decoratorMetadata": decorator_metadata,
+                    "decoratorVersion": decorator_version,
                     "react": {
-                        "runtime": self.react.runtime,
-                        "importSource": self.react.import_source
+                        "runtime": jsx_runtime,
+                        "importSource": jsx_import_source
                     },
                     "optimizer": {
                         // @swc/core@1.2.101+ 支持无需插件实现 @see https://swc.rs/docs/configuration/compilation#jsctransformoptimizerglobals
                         "globals": self.get_globals_from_define(),
                     }
                 },
-                "baseUrl": self.cwd,
-                "paths": self.alias_to_ts_paths(),
                 // @refer: https://rspack.rs/plugins/rspack/swc-js-minimizer-rspack-plugin#minimizeroptions
                 "minify": minify_options,
                 "experimental": {
-                    "plugins": self.get_plugins(),
-                    "cacheRoot": "node_modules/.cache/swc"
+                    // 保留 import attributes: import data from './data.json' with { type: 'json' }
+                    "keepImportAttributes": true,
+                    "plugins": plugins.plugins,
                 }
             },
         });
@@ -362,6 +608,12 @@ impl BundlessOptions {
             "sourceMaps": self.sourcemap,
             "env": {
                 "targets": self.targets
+            },
+            "jsc": {
+                "experimental": {
+                    // wasm plugins 编译缓存 (用户配置已解析为绝对路径)
+                    "cacheRoot": plugins.cache_root,
+                }
             }
         });
         merge_json_values(&mut config_json, &protected_fields);
@@ -382,49 +634,132 @@ impl BundlessOptions {
         )
     }
 
+    /// `tsconfig.json` (含 extends), 首次调用时加载
+    pub(crate) fn resolved_tsconfig(&self) -> Result<Option<&TsConfig>> {
+        if let Some(tsconfig) = self.tsconfig_cache.get() {
+            return Ok(tsconfig.as_ref());
+        }
+
+        let tsconfig = match &self.tsconfig {
+            Some(path) => Some(load_tsconfig(&self.cwd.join(path))?),
+            None => {
+                let path = self.cwd.join("tsconfig.json");
+                if path.is_file() { Some(load_tsconfig(&path)?) } else { None }
+            }
+        };
+
+        Ok(self.tsconfig_cache.get_or_init(|| tsconfig).as_ref())
+    }
+
+    /// `process.env.X`, `import.meta.env.X` define (.env 文件), 首次调用时加载
+    pub(crate) fn env_define(&self) -> Result<&serde_json::Value> {
+        if let Some(define) = self.env_cache.get() {
+            return Ok(define);
+        }
+
+        let define = env_to_define(self)?;
+
+        Ok(self.env_cache.get_or_init(|| define))
+    }
+
+    /// swc Wasm plugins (.wasm 绝对路径) + cacheRoot, 首次调用时解析
+    pub(crate) fn resolved_plugins(&self) -> Result<&ResolvedPlugins> {
+        if let Some(plugins) = self.plugins_cache.get() {
+            return Ok(plugins);
+        }
+
+        let plugins = resolve_plugins_config(&self.cwd, &self.plugins, self.swc_options.as_ref())?;
+
+        Ok(self.plugins_cache.get_or_init(|| plugins))
+    }
+
     pub fn is_node(&self) -> bool {
         self.targets.get("node").is_some()
     }
 
-    fn get_plugins(&self) -> Vec<serde_json::Value> {
-        let plugins = vec![];
+    /// targets.node -> (major, minor), 支持 "20.11.0", "v18", ">=18.12", 18
+    pub(crate) fn node_version(&self) -> Option<(u64, u64)> {
+        let version = match self.targets.get("node")? {
+            serde_json::Value::String(version) => version.clone(),
+            serde_json::Value::Number(version) => version.to_string(),
+            _ => return None,
+        };
 
-        // println!("bundless plugins: {:#?}", &plugins);
+        let version = version.trim_start_matches(|c: char| !c.is_ascii_digit());
+        let mut parts = version.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next().and_then(|minor| minor.parse().ok()).unwrap_or(0);
 
-        plugins
+        Some((major, minor))
     }
 
-    // '@': './src'  -> '@/*': ['./src/*'],
-    // "@alias": ["./src/alias-1", "./src/alias-2"] -> "@alias/*": ["./src/alias-1/*", "./src/alias-2/*"]
-    fn alias_to_ts_paths(&self) -> serde_json::Value {
-        let mut paths = serde_json::Map::new();
+    /// 是否使用 legacy shims (fileURLToPath)
+    ///
+    /// 未显式指定时, node >= 20.11 使用原生 import.meta.dirname/filename
+    pub(crate) fn shims_legacy(&self) -> bool {
+        self.shims
+            .legacy()
+            .unwrap_or_else(|| self.node_version().is_none_or(|version| version < (20, 11)))
+    }
 
+    // alias -> swc_transform_extensions 规则, 路径转为绝对路径
+    // '@': './src' -> find: '@', replacements: ['/demo/src']
+    // '@alias': ['./src/alias-1', './src/alias-2'] -> 依次检查是否存在
+    // 'lodash$': 'lodash-es' -> 包名, 原样替换
+    // 每次构建只计算一次
+    pub(crate) fn alias_rules(&self) -> Result<Vec<AliasRule>> {
+        if let Some(rules) = self.alias_cache.get() {
+            return Ok(rules.clone());
+        }
+
+        let rules = self.build_alias_rules()?;
+
+        Ok(self.alias_cache.get_or_init(|| rules).clone())
+    }
+
+    fn build_alias_rules(&self) -> Result<Vec<AliasRule>> {
+        // 优先级: user alias > tsconfig paths > 默认 '@': './src' (仅未设置 alias 时)
+        let mut paths = match &self.alias {
+            Some(_) => HashMap::new(),
+            None => Alias::default().paths,
+        };
+        if let Some(tsconfig) = self.resolved_tsconfig()? {
+            paths.extend(tsconfig.alias());
+        }
         if let Some(alias) = &self.alias {
-            for (name, alias_path) in &alias.paths {
-                let path_values = match alias_path {
-                    AliasPath::Single(path) => {
-                        vec![format!("{}/*", Self::format_path(path))]
-                    }
-                    AliasPath::Multiple(paths) => {
-                        paths.iter().map(|path| format!("{}/*", Self::format_path(path))).collect()
-                    }
+            paths.extend(alias.paths.clone());
+        }
+
+        let mut rules = paths
+            .iter()
+            .map(|(name, alias_path)| {
+                let paths = match alias_path {
+                    AliasPath::Single(path) => std::slice::from_ref(path),
+                    AliasPath::Multiple(paths) => paths.as_slice(),
                 };
 
-                paths.insert(format!("{name}/*"), json!(path_values));
-            }
-        }
+                let replacements = paths
+                    .iter()
+                    .map(|path| {
+                        if path.starts_with('.') || Path::new(path).is_absolute() {
+                            path_clean::clean(self.cwd.join(path)).to_string_lossy().to_string()
+                        } else {
+                            path.clone()
+                        }
+                    })
+                    .collect();
 
-        serde_json::Value::Object(paths)
-    }
+                let rule = AliasRule { find: name.clone(), replacements };
+                rule.validate().map_err(|e| anyhow::anyhow!(e))?;
 
-    fn format_path(path: &str) -> String {
-        let mut formatted_path = path.trim_end_matches('/').to_string();
+                Ok(rule)
+            })
+            .collect::<Result<Vec<_>>>()?;
 
-        if !formatted_path.starts_with('.') {
-            formatted_path.insert_str(0, "./");
-        }
+        // 更具体(更长)的 key 优先: '@/components' 先于 '@'
+        rules.sort_by(|a, b| b.find.len().cmp(&a.find.len()).then_with(|| a.find.cmp(&b.find)));
 
-        formatted_path
+        Ok(rules)
     }
 
     // webpack define 格式转换成 swc `jsc.transform.optimizer.globals` 配置
@@ -453,3 +788,39 @@ impl BundlessOptions {
         globals
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_dts() {
+        let dts = |value: serde_json::Value| {
+            serde_json::from_value::<BundlessOptions>(json!({ "dts": value })).unwrap().dts.strict
+        };
+
+        assert!(!dts(json!(false)));
+        assert!(!dts(json!(true)));
+        assert!(!dts(json!({})));
+        assert!(dts(json!({ "strict": true })));
+
+        let options = serde_json::from_value::<BundlessOptions>(json!({})).unwrap();
+        assert!(!options.dts.strict);
+    }
+
+    #[test]
+    fn test_clone_caches() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let options = BundlessOptions::default().cwd(fixtures.join("tsconfig"));
+        options.alias_rules().unwrap();
+        assert!(options.alias_cache.get().is_some());
+
+        // clone 后修改 cwd, 不复用原项目的缓存
+        let cloned = options.clone().cwd(fixtures.join("builder"));
+        assert!(cloned.alias_cache.get().is_none());
+        assert!(cloned.resolved_tsconfig().unwrap().is_none());
+
+        // 统计仍共享
+        assert!(Arc::ptr_eq(&options.shims_stats, &cloned.shims_stats));
+    }
+}
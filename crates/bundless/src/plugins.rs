@@ -0,0 +1,179 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+/// swc Wasm plugin: `[name, config]`
+///
+/// name: npm 包名 (从 node_modules 解析 `main` 字段) 或 .wasm 文件路径 (相对 cwd)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Plugin(pub String, #[serde(default)] pub serde_json::Value);
+
+impl Plugin {
+    pub fn new<S: Into<String>>(name: S, config: serde_json::Value) -> Self {
+        Self(name.into(), config)
+    }
+}
+
+// wasm plugins 编译缓存, 相对 cwd
+const DEFAULT_CACHE_ROOT: &str = "node_modules/.cache/swc";
+
+/// `jsc.experimental.plugins` + `cacheRoot`
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ResolvedPlugins {
+    pub plugins: Vec<serde_json::Value>,
+    pub cache_root: PathBuf,
+}
+
+/// 解析 plugins 与 cacheRoot
+///
+/// cacheRoot: 用户 `swcOptions.jsc.experimental.cacheRoot` (相对 cwd), 默认 `node_modules/.cache/swc`
+pub(crate) fn resolve_plugins_config(
+    cwd: &Path,
+    plugins: &[Plugin],
+    swc_options: Option<&serde_json::Value>,
+) -> Result<ResolvedPlugins> {
+    let cache_root = swc_options
+        .and_then(|options| options.pointer("/jsc/experimental/cacheRoot"))
+        .and_then(|cache_root| cache_root.as_str())
+        .unwrap_or(DEFAULT_CACHE_ROOT);
+
+    Ok(ResolvedPlugins {
+        plugins: resolve_plugins(cwd, plugins)?,
+        cache_root: path_clean::clean(cwd.join(cache_root)),
+    })
+}
+
+/// `jsc.experimental.plugins`, plugin 名称解析为 .wasm 绝对路径
+fn resolve_plugins(cwd: &Path, plugins: &[Plugin]) -> Result<Vec<serde_json::Value>> {
+    plugins
+        .iter()
+        .map(|Plugin(name, config)| {
+            let path = resolve_plugin(cwd, name)?;
+            let config = if config.is_null() { serde_json::json!({}) } else { config.clone() };
+            Ok(serde_json::json!([path, config]))
+        })
+        .collect()
+}
+
+// ./plugins/a.wasm -> /cwd/plugins/a.wasm
+// swc-plugin-a -> /cwd/node_modules/swc-plugin-a/<main>
+// swc-plugin-a/dist/a.wasm -> /cwd/node_modules/swc-plugin-a/dist/a.wasm
+fn resolve_plugin(cwd: &Path, name: &str) -> Result<PathBuf> {
+    let path = Path::new(name);
+    if path.is_absolute() || name.starts_with('.') {
+        let path = cwd.join(path);
+        if !path.is_file() {
+            bail!("Could not find swc plugin `{name}` at {}", path.display());
+        }
+        return Ok(path);
+    }
+
+    for dir in cwd.ancestors() {
+        let path = dir.join("node_modules").join(name);
+
+        if path.is_file() {
+            return Ok(path);
+        }
+
+        let pkg_json = path.join("package.json");
+        if pkg_json.is_file() {
+            let content = std::fs::read_to_string(&pkg_json)
+                .with_context(|| format!("Failed to read {}", pkg_json.display()))?;
+            let pkg: serde_json::Value = serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", pkg_json.display()))?;
+
+            let Some(main) = pkg.get("main").and_then(|main| main.as_str()) else {
+                bail!("swc plugin `{name}` has no `main` field in {}", pkg_json.display());
+            };
+
+            let main = path.join(main);
+            if !main.is_file() {
+                bail!("Could not find swc plugin `{name}` at {}", main.display());
+            }
+            return Ok(main);
+        }
+    }
+
+    bail!("Could not resolve swc plugin `{name}` from {}", cwd.display())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BundlessOptions;
+    use crate::swc::transform_file;
+
+    fn fixture(path: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/plugins").join(path)
+    }
+
+    #[test]
+    fn test_resolve_plugins() {
+        let cwd = fixture("src");
+
+        let plugins = resolve_plugins(
+            &cwd,
+            &[
+                Plugin::new("swc-plugin-demo", serde_json::json!({ "a": 1 })),
+                Plugin::new("swc-plugin-demo/plugin.wasm", serde_json::Value::Null),
+                Plugin::new("./local.wasm", serde_json::Value::Null),
+            ],
+        )
+        .unwrap();
+
+        let wasm = fixture("node_modules/swc-plugin-demo/plugin.wasm");
+        assert_eq!(plugins[0], serde_json::json!([wasm, { "a": 1 }]));
+        assert_eq!(plugins[1], serde_json::json!([wasm, {}]));
+        assert_eq!(plugins[2], serde_json::json!([fixture("src/local.wasm"), {}]));
+
+        assert!(resolve_plugin(&cwd, "swc-plugin-missing").is_err());
+        assert!(resolve_plugin(&cwd, "./missing.wasm").is_err());
+    }
+
+    #[test]
+    fn test_cache_root() {
+        let cwd = fixture("src");
+
+        let resolved = resolve_plugins_config(&cwd, &[], None).unwrap();
+        assert_eq!(resolved.cache_root, cwd.join("node_modules/.cache/swc"));
+
+        let swc_options =
+            serde_json::json!({ "jsc": { "experimental": { "cacheRoot": "../.swc" } } });
+        let resolved = resolve_plugins_config(&cwd, &[], Some(&swc_options)).unwrap();
+        assert_eq!(resolved.cache_root, fixture(".swc"));
+
+        // 用户 cacheRoot 解析为绝对路径, 不被原值覆盖
+        let options = BundlessOptions {
+            swc_options: Some(swc_options),
+            ..BundlessOptions::default().cwd(&cwd)
+        };
+        let swc_options = options.build_for_swc().unwrap();
+        assert_eq!(
+            swc_options.config.jsc.experimental.cache_root.as_deref(),
+            Some(fixture(".swc").to_string_lossy().as_ref())
+        );
+    }
+
+    // 需先构建 define 插件: pnpm --filter ./crates/transform_define build
+    #[test]
+    #[ignore = "requires crates/transform_define/swc_plugin_transform_define.wasm"]
+    fn test_load_plugin() {
+        let wasm = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../transform_define/swc_plugin_transform_define.wasm");
+
+        let options = BundlessOptions {
+            out_dir: Some(std::env::temp_dir().join("lecp-bundless-plugins")),
+            ..BundlessOptions::default().cwd(fixture("")).plugins(vec![Plugin::new(
+                wasm.to_string_lossy(),
+                serde_json::json!({ "PLUGIN_VERSION": "1.0.0" }),
+            )])
+        };
+
+        let file = fixture("src/index.ts");
+        let swc_options = options.build_for_swc().unwrap();
+        let code = transform_file(&file, None, &swc_options, &options).unwrap().code;
+
+        assert!(code.contains(r#"version = "1.0.0""#), "{code}");
+    }
+}
@@ -0,0 +1,249 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use log::{info, warn};
+use owo_colors::OwoColorize;
+use swc_transform_shims::ShimsUsage;
+
+use crate::{BundlessOptions, ModuleType};
+
+/// `swc_transform_shims` config, None: 未开启 shims
+///
+/// 非 node targets 使用 browser 模式, 不注入 node shims
+pub(crate) fn shims_config(
+    bundless_options: &BundlessOptions,
+) -> Option<swc_transform_shims::Config> {
+    if !bundless_options.shims.is_enabled() {
+        return None;
+    }
+
+    let platform = if bundless_options.is_node() {
+        swc_transform_shims::Platform::Node
+    } else {
+        swc_transform_shims::Platform::Browser
+    };
+
+    let target = match bundless_options.format {
+        ModuleType::ESM => swc_transform_shims::Target::ESM,
+        ModuleType::CJS => swc_transform_shims::Target::CJS,
+    };
+
+    Some(swc_transform_shims::Config {
+        legacy: bundless_options.shims_legacy(),
+        target,
+        interop: bundless_options.shims.interop(),
+        // shims 在 cjs 模块转换后执行, 以下由 before pass 处理, 见 [cjs_interop_enabled],
+        // [cjs_module_syntax_enabled]
+        cjs_interop: false,
+        top_level_await: bundless_options.shims.top_level_await(),
+        skip_module_syntax: true,
+        platform,
+        polyfill: bundless_options.shims.polyfill(),
+    })
+}
+
+/// CJS: export default -> module.exports = default (需在 cjs 模块转换前执行)
+pub(crate) fn cjs_interop_enabled(bundless_options: &BundlessOptions) -> bool {
+    bundless_options.shims.is_enabled()
+        && bundless_options.shims.cjs_interop()
+        && matches!(bundless_options.format, ModuleType::CJS)
+}
+
+/// CJS: top-level await, import.meta (node) 需在 cjs 模块转换前处理
+/// - 转换后 export 变为 `exports.x = ...` 赋值, 无法再检查 top-level await 与导出的冲突
+/// - swc 的 commonjs 转换会改写 import.meta
+pub(crate) fn cjs_module_syntax_enabled(bundless_options: &BundlessOptions) -> bool {
+    bundless_options.shims.is_enabled() && matches!(bundless_options.format, ModuleType::CJS)
+}
+
+/// Record the shims applied to `file` (跨文件汇总)
+pub(crate) fn record_shims_usage(
+    options: &BundlessOptions,
+    file: &Path,
+    usage: &Arc<Mutex<ShimsUsage>>,
+) {
+    let usage = usage.lock().unwrap();
+    if usage.is_empty() {
+        return;
+    }
+
+    let file = file.strip_prefix(&options.cwd).unwrap_or(file).to_path_buf();
+    options.shims_stats.lock().unwrap().insert(file, usage.clone());
+}
+
+/// Print the shims applied per file, warn about `require` in esm output for browser targets and
+/// skipped cjs interop
+pub(crate) fn report_shims_usage(options: &BundlessOptions) {
+    let stats = options.shims_stats.lock().unwrap();
+    let browser_esm = !options.is_node() && matches!(options.format, ModuleType::ESM);
+
+    for (file, usage) in stats.iter() {
+        let shims = usage
+            .hits
+            .iter()
+            .map(|(name, count)| format!("{name} x{count}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if !usage.hits.is_empty() {
+            info!("shims({}) {}: {}", options.format.get_type(), file.display().yellow(), shims);
+        }
+
+        for msg in &usage.warnings {
+            warn!("{}: {}", file.display().yellow(), msg);
+        }
+
+        if browser_esm && usage.contains("require") {
+            warn!(
+                "{} uses {} in esm output for browser targets, it is not available in browsers",
+                file.display().yellow(),
+                "require".yellow()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{Shims, build_file};
+
+    #[test]
+    fn test_shims_config() {
+        let options = |targets: serde_json::Value, shims: Shims| {
+            BundlessOptions::default().targets(targets).shims(shims)
+        };
+        let legacy = |targets: serde_json::Value, shims: Shims| {
+            shims_config(&options(targets, shims)).map(|config| config.legacy)
+        };
+
+        // 未开启
+        assert_eq!(legacy(serde_json::json!({ "node": "18" }), Shims::Boolean(false)), None);
+
+        // 浏览器
+        let config =
+            shims_config(&options(serde_json::json!({ "chrome": "100" }), Shims::Boolean(true)))
+                .unwrap();
+        assert_eq!(config.platform, swc_transform_shims::Platform::Browser);
+
+        // 根据 node 版本自动选择
+        assert_eq!(
+            legacy(serde_json::json!({ "node": "18.12.0" }), Shims::Boolean(true)),
+            Some(true)
+        );
+        assert_eq!(
+            legacy(serde_json::json!({ "node": "20.10" }), Shims::Boolean(true)),
+            Some(true)
+        );
+        assert_eq!(
+            legacy(serde_json::json!({ "node": ">=20.11" }), Shims::Boolean(true)),
+            Some(false)
+        );
+        assert_eq!(legacy(serde_json::json!({ "node": 22 }), Shims::Boolean(true)), Some(false));
+
+        // 显式指定
+        assert_eq!(
+            legacy(
+                serde_json::json!({ "node": "22" }),
+                serde_json::from_value(serde_json::json!({ "legacy": true })).unwrap()
+            ),
+            Some(true)
+        );
+        assert_eq!(
+            legacy(
+                serde_json::json!({ "node": "16" }),
+                serde_json::from_value(serde_json::json!({ "legacy": null })).unwrap()
+            ),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_record_shims_usage() {
+        let options = BundlessOptions::default();
+        let file = options.cwd.join("src/index.ts");
+        let usage = Arc::new(Mutex::new(ShimsUsage::default()));
+
+        // 未使用 shims 的文件不记录
+        record_shims_usage(&options, &file, &usage);
+        assert!(options.shims_stats.lock().unwrap().is_empty());
+
+        usage.lock().unwrap().record("require");
+        usage.lock().unwrap().record("createRequire");
+        record_shims_usage(&options, &file, &usage);
+
+        let stats = options.shims_stats.lock().unwrap();
+        let usage = &stats[Path::new("src/index.ts")];
+        assert!(usage.contains("require"));
+        assert!(usage.contains("createRequire"));
+        assert!(!usage.contains("__dirname"));
+    }
+
+    #[test]
+    fn test_cjs_interop_enabled() {
+        let shims: Shims =
+            serde_json::from_value(serde_json::json!({ "cjsInterop": true })).unwrap();
+
+        assert!(cjs_interop_enabled(
+            &BundlessOptions::default().format(ModuleType::CJS).shims(shims.clone())
+        ));
+        assert!(!cjs_interop_enabled(
+            &BundlessOptions::default().format(ModuleType::ESM).shims(shims)
+        ));
+        assert!(!cjs_interop_enabled(
+            &BundlessOptions::default().format(ModuleType::CJS).shims(Shims::Boolean(true))
+        ));
+    }
+
+    fn fixture(path: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/shims").join(path)
+    }
+
+    fn cjs_options(shims: Shims) -> BundlessOptions {
+        BundlessOptions {
+            out_dir: Some(std::env::temp_dir().join("lecp-bundless-shims")),
+            ..BundlessOptions::default()
+                .cwd(fixture(""))
+                .format(ModuleType::CJS)
+                .targets(serde_json::json!({ "node": "18" }))
+                .shims(shims)
+        }
+    }
+
+    #[test]
+    fn test_cjs_import_meta() {
+        let options = cjs_options(Shims::Boolean(true));
+
+        let out_path = build_file(&fixture("src/meta.ts"), &options).unwrap().unwrap();
+        let code = std::fs::read_to_string(out_path).unwrap();
+        assert!(!code.contains("import.meta"));
+        assert!(code.contains("__dirname"));
+
+        // 由 before pass 处理 (先于 swc 的 commonjs 转换)
+        let stats = options.shims_stats.lock().unwrap();
+        let usage = &stats[Path::new("src/meta.ts")];
+        assert!(usage.contains("import.meta.url"));
+        assert!(usage.contains("import.meta.dirname"));
+    }
+
+    #[test]
+    fn test_cjs_top_level_await() {
+        let shims: Shims =
+            serde_json::from_value(serde_json::json!({ "topLevelAwait": true })).unwrap();
+        let options = cjs_options(shims);
+
+        // 类型导出不影响包裹
+        let out_path = build_file(&fixture("src/await.ts"), &options).unwrap().unwrap();
+        let code = std::fs::read_to_string(out_path).unwrap();
+        let iife = code.find("async").unwrap();
+        assert!(code.find("await").unwrap() > iife);
+
+        // 导出在包裹后将异步赋值, 报错
+        assert!(build_file(&fixture("src/await-export.ts"), &options).is_err());
+
+        // 未开启 topLevelAwait 时报错
+        let options = cjs_options(Shims::Boolean(true));
+        assert!(build_file(&fixture("src/await.ts"), &options).is_err());
+    }
+}
@@ -1,17 +1,30 @@
 use std::collections::HashMap;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use anyhow::{Context, Result};
 use log::debug;
 use swc_core::base::config::Options;
 use swc_core::base::{Compiler, TransformOutput, try_with_handler};
 use swc_core::common::comments::SingleThreadedComments;
-use swc_core::common::{GLOBALS, SourceMap};
+use swc_core::common::{FileName, GLOBALS, SourceMap};
 use swc_core::ecma::ast::{Pass, noop_pass};
-
+use swc_transform_extensions::ResolvedSpecifiers;
+use swc_transform_shims::ShimsUsage;
+
+use crate::BundlessOptions;
+use crate::builder::PassPhase;
+use crate::define::user_define_keys;
+use crate::hooks::{ResolveIdError, resolve_id_pass};
+use crate::json::json_import_mode;
+use crate::shims::{
+    cjs_interop_enabled, cjs_module_syntax_enabled, record_shims_usage, shims_config,
+};
 use crate::util::write_file;
-use crate::{BundlessOptions, ModuleType};
+
+// 解析无后缀导入时依次尝试的源文件后缀
+pub(crate) const RESOLVE_EXTENSIONS: [&str; 8] =
+    [".ts", ".tsx", ".mts", ".cts", ".js", ".jsx", ".mjs", ".cjs"];
 
 // !options.config.error.filename -> skip_filename:true
 // handler config
@@ -40,8 +53,38 @@ use crate::{BundlessOptions, ModuleType};
 //     Ok(())
 // }
 
+/// `swc_transform_extensions` config for `file`: `.js`, `.mjs`, `.cjs` (and `.less`) -> out ext
+///
+/// 不含 alias: alias 只由 before pass 的 alias 处理 (见 [BundlessOptions::alias_rules])
+pub(crate) fn extensions_config(
+    file: &Path,
+    bundless_options: &BundlessOptions,
+) -> Result<swc_transform_extensions::Config> {
+    let out_ext = format!(".{}", bundless_options.out_ext());
+    let mut extensions_map = HashMap::from([
+        (".js".to_string(), out_ext.clone()),
+        (".mjs".to_string(), out_ext.clone()),
+        (".cjs".to_string(), out_ext.clone()),
+    ]);
+
+    if let Some(css) = &bundless_options.css {
+        if css.less_compile {
+            extensions_map.insert(".less".to_string(), ".css".to_string());
+        }
+    }
+
+    Ok(swc_transform_extensions::Config {
+        extensions: extensions_map,
+        source_dir: Some(bundless_options.src_dir().to_string_lossy().to_string()),
+        current_dir: file.parent().map(|p| p.to_string_lossy().to_string()),
+        ..Default::default()
+    })
+}
+
+/// source: `transform` 钩子返回的源码, None 时读取 `file`
 pub fn transform_file(
     file: &Path,
+    source: Option<String>,
     options: &Options,
     bundless_options: &BundlessOptions,
 ) -> Result<TransformOutput> {
@@ -50,13 +93,27 @@ pub fn transform_file(
 
     debug!("swc options: {options:#?}");
 
+    // import.meta.env.X, process.env.X (.env files)
+    let env_define = bundless_options.env_define()?.clone();
+    let extensions_config = extensions_config(file, bundless_options)?;
+    let alias = bundless_options.alias_rules()?;
+    let shims_usage = Arc::new(Mutex::new(ShimsUsage::default()));
+    let resolved_specifiers = ResolvedSpecifiers::default();
+    let resolve_id_error = ResolveIdError::default();
+    let passes = &bundless_options.passes;
+
     // 计算 SyntaxContext
-    GLOBALS
+    let output = GLOBALS
         .set(&Default::default(), || {
             try_with_handler(cm.clone(), Default::default(), |handler| {
                 debug!("load file {file:?}");
 
-                let fm = cm.load_file(file).context("swc failed to load file")?;
+                let fm = match source {
+                    Some(code) => {
+                        cm.new_source_file(FileName::Real(file.to_path_buf()).into(), code)
+                    }
+                    None => cm.load_file(file).context("swc failed to load file")?,
+                };
                 // compiler.process_js_file(fm, handler, options).context("failed to process file")
                 compiler
                     .process_js_with_custom_pass(
@@ -66,77 +123,117 @@ pub fn transform_file(
                         options,
                         SingleThreadedComments::default(),
                         |_| {
-                            // ts2js (由于 cjs 插件暂不读取 require，需要提前转换ts -> js)
+                            // define: env 变量 (用户 define 由 optimizer.globals 替换)
+                            let define_pass = swc_transform_define::transform(env_define);
+
+                            // 用户 define 引用统计 (只读, 先于 optimizer.globals)
+                            let define_stats_pass = swc_transform_define::collect_stats(
+                                user_define_keys(bundless_options),
+                                bundless_options.define_stats.clone(),
+                            );
+
+                            // alias (先于 ts2js, alias 可能指向 .ts 文件): @/utils -> ./utils
+                            // alias 仅在此处理, after pass 的 extensions 不再重复
+                            let alias_pass =
+                                swc_transform_extensions::alias(swc_transform_extensions::Config {
+                                    resolve_extensions: RESOLVE_EXTENSIONS
+                                        .map(String::from)
+                                        .to_vec(),
+                                    alias: alias.clone(),
+                                    ..extensions_config.clone()
+                                });
+
+                            // 补全导入后缀 (产物后缀): ./utils -> ./utils.mjs, ./components -> ./components/index.js
+                            // 已补全的 specifier 不再由 after pass 的 extensions 替换
+                            let resolve_extensions_pass =
+                                swc_transform_extensions::resolve_extensions(
+                                    swc_transform_extensions::Config {
+                                        resolve_extensions: RESOLVE_EXTENSIONS
+                                            .map(String::from)
+                                            .to_vec(),
+                                        ..extensions_config.clone()
+                                    },
+                                    resolved_specifiers.clone(),
+                                );
+
+                            // ts2js (需在 cjs 模块转换前处理 import，require 由 after pass 的 extensions 兜底)
                             let ts2js_pass =
                                 swc_transform_ts2js::transform(swc_transform_ts2js::Config {
                                     preserve_import_extension: Default::default(),
                                 });
 
-                            // extensions
-                            let out_ext = format!(".{}", bundless_options.out_ext());
-                            let mut extensions_map = HashMap::from([
-                                (".js".to_string(), out_ext.clone()),
-                                (".mjs".to_string(), out_ext.clone()),
-                                (".cjs".to_string(), out_ext.clone()),
-                            ]);
-
-                            if let Some(css) = &bundless_options.css {
-                                if css.less_compile {
-                                    extensions_map.insert(".less".to_string(), ".css".to_string());
-                                }
-                            }
-
-                            let extensions_pass = swc_transform_extensions::transform(
-                                swc_transform_extensions::Config {
-                                    extensions: extensions_map,
-                                    source_dir: Some(
-                                        bundless_options.src_dir().to_string_lossy().to_string(),
-                                    ),
-                                    current_dir: file
-                                        .parent()
-                                        .map(|p| p.to_string_lossy().to_string()),
+                            // cjs interop: export default -> module.exports = default
+                            let cjs_interop_pass = if cjs_interop_enabled(bundless_options) {
+                                Box::new(swc_transform_shims::cjs_interop(Some(
+                                    shims_usage.clone(),
+                                ))) as Box<dyn Pass>
+                            } else {
+                                Box::new(noop_pass()) as Box<dyn Pass>
+                            };
+
+                            // top-level await, import.meta (cjs): 需在 cjs 模块转换前处理
+                            let cjs_module_syntax = cjs_module_syntax_enabled(bundless_options);
+                            let top_level_await_pass = if cjs_module_syntax {
+                                Box::new(swc_transform_shims::top_level_await(
+                                    bundless_options.shims.top_level_await(),
+                                )) as Box<dyn Pass>
+                            } else {
+                                Box::new(noop_pass()) as Box<dyn Pass>
+                            };
+                            let import_meta_pass =
+                                if cjs_module_syntax && bundless_options.is_node() {
+                                    Box::new(swc_transform_shims::import_meta(Some(
+                                        shims_usage.clone(),
+                                    ))) as Box<dyn Pass>
+                                } else {
+                                    Box::new(noop_pass()) as Box<dyn Pass>
+                                };
+
+                            // resolveId 钩子 (先于 alias)
+                            let resolve_id_pass = bundless_options.hooks.get().map_or_else(
+                                || Box::new(noop_pass()) as Box<dyn Pass>,
+                                |hooks| {
+                                    Box::new(resolve_id_pass(
+                                        hooks.clone(),
+                                        file,
+                                        resolve_id_error.clone(),
+                                    )) as Box<dyn Pass>
                                 },
                             );
 
-                            (ts2js_pass, extensions_pass)
+                            (
+                                resolve_id_pass,
+                                define_stats_pass,
+                                passes.builtin("define", define_pass),
+                                passes.builtin("alias", alias_pass),
+                                passes.builtin("resolve-extensions", resolve_extensions_pass),
+                                passes.builtin("ts2js", ts2js_pass),
+                                passes.builtin("cjs-interop", cjs_interop_pass),
+                                passes.builtin("top-level-await", top_level_await_pass),
+                                passes.builtin("import-meta", import_meta_pass),
+                                passes.custom(PassPhase::Before, file, bundless_options),
+                            )
                         },
                         |_| {
                             // shims
-                            let shims_target = match bundless_options.format {
-                                ModuleType::ESM => swc_transform_shims::Target::ESM,
-                                ModuleType::CJS => swc_transform_shims::Target::CJS,
-                            };
+                            let shims_pass = shims_config(bundless_options).map_or_else(
+                                || Box::new(noop_pass()) as Box<dyn Pass>,
+                                |config| {
+                                    Box::new(swc_transform_shims::transform_with_stats(
+                                        config,
+                                        shims_usage.clone(),
+                                    )) as Box<dyn Pass>
+                                },
+                            );
 
-                            let shims_pass =
-                                swc_transform_shims::transform(swc_transform_shims::Config {
-                                    legacy: true,
-                                    target: shims_target,
-                                });
+                            // json: import attributes / createRequire
+                            let json_pass =
+                                crate::json::transform(json_import_mode(bundless_options));
 
                             // extensions
-                            let out_ext = format!(".{}", bundless_options.out_ext());
-                            let mut extensions_map = HashMap::from([
-                                (".js".to_string(), out_ext.clone()),
-                                (".mjs".to_string(), out_ext.clone()),
-                                (".cjs".to_string(), out_ext.clone()),
-                            ]);
-
-                            if let Some(css) = &bundless_options.css {
-                                if css.less_compile {
-                                    extensions_map.insert(".less".to_string(), ".css".to_string());
-                                }
-                            }
-
-                            let extensions_pass = swc_transform_extensions::transform(
-                                swc_transform_extensions::Config {
-                                    extensions: extensions_map,
-                                    source_dir: Some(
-                                        bundless_options.src_dir().to_string_lossy().to_string(),
-                                    ),
-                                    current_dir: file
-                                        .parent()
-                                        .map(|p| p.to_string_lossy().to_string()),
-                                },
+                            let extensions_pass = swc_transform_extensions::transform_with_resolved(
+                                extensions_config.clone(),
+                                resolved_specifiers.clone(),
                             );
 
                             // css modules
@@ -160,13 +257,27 @@ pub fn transform_file(
                                     },
                                 );
 
-                            (extensions_pass, shims_pass, css_modules_pass)
+                            (
+                                passes.builtin("json", json_pass),
+                                passes.builtin("extensions", extensions_pass),
+                                passes.builtin("shims", shims_pass),
+                                passes.builtin("css-modules", css_modules_pass),
+                                passes.custom(PassPhase::After, file, bundless_options),
+                            )
                         },
                     )
                     .context("swc failed to process file")
             })
         })
-        .map_err(|e| e.to_pretty_error())
+        .map_err(|e| e.to_pretty_error())?;
+
+    if let Some(e) = resolve_id_error.lock().unwrap().take() {
+        return Err(e);
+    }
+
+    record_shims_usage(bundless_options, file, &shims_usage);
+
+    Ok(output)
 }
 
 // pub fn transform(code: String, options: &Options) -> Result<TransformOutput> {
@@ -238,10 +349,31 @@ mod tests {
     //     }
     // }
 
+    // Button.tsx 固定输出 Button.js, 不随 out_ext 替换为 .mjs
+    #[test]
+    fn test_transform_file_resolve_extensions() {
+        let cwd = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/resolve");
+        let options = BundlessOptions::default()
+            .cwd(&cwd)
+            .format(crate::ModuleType::ESM)
+            .is_module(false)
+            .targets(serde_json::json!({ "node": "22" }));
+        assert_eq!(options.out_ext(), "mjs");
+
+        let swc_options = options.build_for_swc().unwrap();
+        let code =
+            transform_file(&cwd.join("src/index.ts"), None, &swc_options, &options).unwrap().code;
+
+        assert!(code.contains("\"./Button.js\""), "{code}");
+        assert!(code.contains("\"./components/index.js\""), "{code}");
+        assert!(code.contains("\"./utils.mjs\""), "{code}");
+    }
+
     #[test]
     fn test_transform_file_not_found() {
         let result = transform_file(
             Path::new("./transform-input-404.js"),
+            None,
             &Default::default(),
             &Default::default(),
         );
@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use log::debug;
+use serde::Deserialize;
+
+use crate::options::{AliasPath, JsxRuntime};
+use crate::util::serde_error_to_miette;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTsConfig {
+    #[serde(default)]
+    extends: Option<Extends>,
+    #[serde(default)]
+    compiler_options: RawCompilerOptions,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Extends {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawCompilerOptions {
+    base_url: Option<String>,
+    paths: Option<HashMap<String, Vec<String>>>,
+    jsx: Option<String>,
+    jsx_import_source: Option<String>,
+    experimental_decorators: Option<bool>,
+    emit_decorator_metadata: Option<bool>,
+}
+
+/// compilerOptions (合并 extends 之后), 路径均为绝对路径
+#[derive(Debug, Default, Clone)]
+pub struct TsConfig {
+    pub base_url: Option<PathBuf>,
+    pub paths: Option<HashMap<String, Vec<String>>>,
+    /// 未设置 baseUrl 时, paths 相对于定义它的 tsconfig 所在目录
+    paths_dir: Option<PathBuf>,
+    pub jsx: Option<String>,
+    pub jsx_import_source: Option<String>,
+    pub experimental_decorators: Option<bool>,
+    pub emit_decorator_metadata: Option<bool>,
+}
+
+impl TsConfig {
+    // 后者覆盖前者
+    fn merge(&mut self, other: TsConfig) {
+        if other.base_url.is_some() {
+            self.base_url = other.base_url;
+        }
+        if other.paths.is_some() {
+            self.paths = other.paths;
+            self.paths_dir = other.paths_dir;
+        }
+        if other.jsx.is_some() {
+            self.jsx = other.jsx;
+        }
+        if other.jsx_import_source.is_some() {
+            self.jsx_import_source = other.jsx_import_source;
+        }
+        if other.experimental_decorators.is_some() {
+            self.experimental_decorators = other.experimental_decorators;
+        }
+        if other.emit_decorator_metadata.is_some() {
+            self.emit_decorator_metadata = other.emit_decorator_metadata;
+        }
+    }
+
+    /// - react-jsx, react-jsxdev -> automatic
+    /// - react -> classic
+    /// - preserve, react-native -> preserve
+    pub fn jsx_runtime(&self) -> Option<JsxRuntime> {
+        match self.jsx.as_deref()? {
+            "react-jsx" | "react-jsxdev" => Some(JsxRuntime::Automatic),
+            "react" => Some(JsxRuntime::Classic),
+            "preserve" | "react-native" => Some(JsxRuntime::Preserve),
+            _ => None,
+        }
+    }
+
+    /// compilerOptions.paths -> alias (绝对路径)
+    /// - `"@/*": ["./src/*"]` -> `"@": ["/demo/src"]`
+    /// - `"jquery": ["./vendor/jquery.js"]` -> `"jquery$": ["/demo/vendor/jquery.js"]`
+    /// - `"*.css": ["./styles/*.css"]` -> `"/^(.*)\\.css$/": ["/demo/styles/$1.css"]`
+    pub fn alias(&self) -> HashMap<String, AliasPath> {
+        let Some(paths) = &self.paths else {
+            return HashMap::new();
+        };
+
+        let Some(base) = self.base_url.as_ref().or(self.paths_dir.as_ref()) else {
+            return HashMap::new();
+        };
+
+        let resolve =
+            |value: &str| path_clean::clean(base.join(value)).to_string_lossy().to_string();
+
+        paths
+            .iter()
+            .filter_map(|(key, values)| {
+                let (name, targets) = match key.split_once('*') {
+                    None => {
+                        (format!("{key}$"), values.iter().map(|value| resolve(value)).collect())
+                    }
+                    Some((prefix, "")) if prefix.ends_with('/') => {
+                        let targets = values
+                            .iter()
+                            .filter_map(|value| value.strip_suffix("/*"))
+                            .map(resolve)
+                            .collect::<Vec<_>>();
+                        (prefix.trim_end_matches('/').to_string(), targets)
+                    }
+                    Some((prefix, suffix)) => {
+                        let targets = values
+                            .iter()
+                            .filter_map(|value| value.split_once('*'))
+                            .map(|(p, s)| {
+                                // "*": ["./types/*"] -> "/demo/types/$1"
+                                let dir = resolve(if p.is_empty() { "." } else { p });
+                                let sep = if p.is_empty() || p.ends_with('/') { "/" } else { "" };
+                                format!("{dir}{sep}$1{s}")
+                            })
+                            .collect::<Vec<_>>();
+                        (
+                            format!("/^{}(.*){}$/", regex::escape(prefix), regex::escape(suffix)),
+                            targets,
+                        )
+                    }
+                };
+
+                (!targets.is_empty()).then_some((name, AliasPath::Multiple(targets)))
+            })
+            .collect()
+    }
+}
+
+/// Load `tsconfig.json`, following `extends`
+pub fn load_tsconfig(path: &Path) -> Result<TsConfig> {
+    load_tsconfig_inner(path, &mut vec![])
+}
+
+fn load_tsconfig_inner(path: &Path, visited: &mut Vec<PathBuf>) -> Result<TsConfig> {
+    let path = path_clean::clean(path);
+    if visited.contains(&path) {
+        return Err(anyhow!("circular tsconfig extends: {}", path.display()));
+    }
+    visited.push(path.clone());
+
+    debug!("load tsconfig {path:?}");
+
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("failed to read tsconfig {}", path.display()))?;
+    let content = strip_jsonc(&content);
+
+    let raw = serde_json::from_str::<RawTsConfig>(&content).map_err(|e| {
+        let msg = format!("Could not parse {}", path.display());
+        let miette_err = serde_error_to_miette(e, &content, &msg);
+        anyhow!("{:?}", miette_err)
+    })?;
+
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+    let mut tsconfig = TsConfig::default();
+
+    let extends = match raw.extends {
+        Some(Extends::Single(extends)) => vec![extends],
+        Some(Extends::Multiple(extends)) => extends,
+        None => vec![],
+    };
+    for extends in extends {
+        let base_path = resolve_extends(&extends, &dir).ok_or_else(|| {
+            anyhow!("cannot find tsconfig {extends} extended by {}", path.display())
+        })?;
+        tsconfig.merge(load_tsconfig_inner(&base_path, visited)?);
+    }
+
+    let options = raw.compiler_options;
+    tsconfig.merge(TsConfig {
+        base_url: options.base_url.map(|base_url| path_clean::clean(dir.join(base_url))),
+        paths_dir: options.paths.as_ref().map(|_| dir.clone()),
+        paths: options.paths,
+        jsx: options.jsx,
+        jsx_import_source: options.jsx_import_source,
+        experimental_decorators: options.experimental_decorators,
+        emit_decorator_metadata: options.emit_decorator_metadata,
+    });
+
+    visited.pop();
+
+    Ok(tsconfig)
+}
+
+// ./tsconfig.base.json, ./tsconfig.base, @tsconfig/node20/tsconfig.json, @tsconfig/strictest
+fn resolve_extends(extends: &str, dir: &Path) -> Option<PathBuf> {
+    let candidates = |base: PathBuf| {
+        let with_json = PathBuf::from(format!("{}.json", base.to_string_lossy()));
+        let index = base.join("tsconfig.json");
+        [base, with_json, index]
+    };
+
+    if extends.starts_with('.') || Path::new(extends).is_absolute() {
+        return candidates(dir.join(extends)).into_iter().find(|path| path.is_file());
+    }
+
+    dir.ancestors()
+        .flat_map(|ancestor| candidates(ancestor.join("node_modules").join(extends)))
+        .find(|path| path.is_file())
+}
+
+/// tsconfig.json 允许注释和尾逗号
+pub(crate) fn strip_jsonc(content: &str) -> String {
+    let chars = content.chars().collect::<Vec<_>>();
+    let mut result = String::with_capacity(content.len());
+    let mut i = 0;
+    let mut in_string = false;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if in_string {
+            result.push(c);
+            if c == '\\' {
+                if let Some(&next) = chars.get(i + 1) {
+                    result.push(next);
+                    i += 1;
+                }
+            } else if c == '"' {
+                in_string = false;
+            }
+            i += 1;
+            continue;
+        }
+
+        match (c, chars.get(i + 1)) {
+            ('"', _) => {
+                in_string = true;
+                result.push(c);
+                i += 1;
+            }
+            // 保留换行, 错误提示的行号不变
+            ('/', Some('/')) => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            ('/', Some('*')) => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    if chars[i] == '\n' {
+                        result.push('\n');
+                    }
+                    i += 1;
+                }
+                i += 2;
+            }
+            (',', _) => {
+                let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+                if !matches!(next, Some('}') | Some(']')) {
+                    result.push(c);
+                }
+                i += 1;
+            }
+            _ => {
+                result.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(path: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tsconfig").join(path)
+    }
+
+    #[test]
+    fn test_strip_jsonc() {
+        let content = r#"{
+            // comment
+            "a": "http://example.com", /* block */
+            "b": [1, 2,],
+        }"#;
+
+        let value: serde_json::Value = serde_json::from_str(&strip_jsonc(content)).unwrap();
+        assert_eq!(value, serde_json::json!({ "a": "http://example.com", "b": [1, 2] }));
+    }
+
+    #[test]
+    fn test_load_tsconfig_extends() {
+        let tsconfig = load_tsconfig(&fixture("tsconfig.json")).unwrap();
+
+        assert_eq!(tsconfig.jsx_runtime(), Some(JsxRuntime::Classic));
+        assert_eq!(tsconfig.experimental_decorators, Some(true));
+        assert_eq!(tsconfig.emit_decorator_metadata, Some(false));
+        assert_eq!(tsconfig.base_url, Some(fixture("")));
+
+        let alias = tsconfig.alias();
+        let targets = |name: &str| match &alias[name] {
+            AliasPath::Multiple(targets) => targets.clone(),
+            AliasPath::Single(target) => vec![target.clone()],
+        };
+
+        assert_eq!(targets("@"), vec![fixture("src").to_string_lossy().to_string()]);
+        assert_eq!(
+            targets("jquery$"),
+            vec![fixture("vendor/jquery.js").to_string_lossy().to_string()]
+        );
+        assert_eq!(
+            targets("/^(.*)\\.css$/"),
+            vec![format!("{}/$1.css", fixture("styles").to_string_lossy())]
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result, anyhow};
+use log::warn;
+use miette::{LabeledSpan, NamedSource, miette};
+use owo_colors::OwoColorize;
+use rayon::prelude::*;
+use swc_core::common::{SourceMap, Span};
+use swc_core::ecma::ast::{self, EsVersion};
+use swc_core::ecma::parser::{EsSyntax, Syntax, TsSyntax, parse_file_as_program};
+use swc_core::ecma::visit::{Visit, VisitWith};
+use swc_transform_extensions::specifier::Bindings;
+
+use crate::BundlessOptions;
+
+// 产物中的 js 后缀 -> 对应的声明文件后缀
+const DTS_EXTENSIONS: [(&str, &str); 3] =
+    [(".js", ".d.ts"), (".mjs", ".d.mts"), (".cjs", ".d.cts")];
+
+/// 检查产物中的相对路径 import 是否都指向已生成的文件 (js 或 .d.ts)
+///
+/// - files: 本次构建生成的产物, out_dir 中的其他文件 (旧产物, 手写文件) 不检查
+/// - 只检查 js 模块 (无后缀, .js, .mjs, .cjs), 静态资源 (.css, .json, ...) 不由 bundless 生成
+pub(crate) fn verify_outputs(
+    options: &BundlessOptions,
+    files: &[PathBuf],
+    dts: bool,
+) -> Result<()> {
+    let broken = files
+        .par_iter()
+        .map(|file| {
+            let broken = broken_imports(file, dts)?;
+
+            if !broken.is_empty() {
+                let name = file.strip_prefix(&options.cwd).unwrap_or(file).display().to_string();
+                let content = std::fs::read_to_string(file)?;
+                let labels = broken
+                    .iter()
+                    .map(|(specifier, range)| {
+                        let message = format!("cannot resolve {specifier}");
+                        match range {
+                            Some(range) => LabeledSpan::at(range.clone(), message),
+                            None => LabeledSpan::at_offset(0, message),
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                let report = miette!(labels = labels, "broken relative imports in {name}")
+                    .with_source_code(NamedSource::new(name, content));
+                warn!("{:?}", report);
+            }
+
+            Ok(broken.len())
+        })
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .sum::<usize>();
+
+    if broken > 0 {
+        warn!("{} relative imports do not resolve to an emitted file", broken.yellow());
+    }
+
+    Ok(())
+}
+
+/// (specifier, 源码中的字节范围; dummy span 时为 None)
+fn broken_imports(file: &Path, dts: bool) -> Result<Vec<(String, Option<Range<usize>>)>> {
+    let cm = Arc::<SourceMap>::default();
+    let fm = cm.load_file(file).with_context(|| format!("failed to load {}", file.display()))?;
+
+    let syntax = if dts {
+        Syntax::Typescript(TsSyntax { dts: true, ..Default::default() })
+    } else {
+        // jsx: preserve 时产物中保留 jsx
+        Syntax::Es(EsSyntax { jsx: true, ..Default::default() })
+    };
+
+    let program = match parse_file_as_program(&fm, syntax, EsVersion::latest(), None, &mut vec![]) {
+        Ok(program) => program,
+        Err(e) => {
+            warn!("skip verifying {}: {}", file.display(), e.kind().msg());
+            return Ok(vec![]);
+        }
+    };
+
+    let mut collector = CollectSpecifiers { bindings: Bindings::new(&program), specifiers: vec![] };
+    program.visit_with(&mut collector);
+
+    let current_dir = file.parent().ok_or_else(|| anyhow!("invalid file {}", file.display()))?;
+
+    let broken = collector
+        .specifiers
+        .into_iter()
+        .filter(|(specifier, _)| !resolves(specifier, current_dir, dts))
+        .map(|(specifier, span)| {
+            let range = if span.is_dummy() {
+                None
+            } else {
+                span.lo.0.checked_sub(fm.start_pos.0).map(|start| {
+                    let end = span.hi.0.saturating_sub(fm.start_pos.0).max(start);
+                    start as usize..end as usize
+                })
+            };
+            (specifier, range)
+        })
+        .collect();
+
+    Ok(broken)
+}
+
+fn resolves(specifier: &str, current_dir: &Path, dts: bool) -> bool {
+    let path = path_clean::clean(current_dir.join(specifier));
+    let path_str = path.to_string_lossy();
+
+    let ext = path.extension().map(|ext| format!(".{}", ext.to_string_lossy()));
+    let is_js = match ext.as_deref() {
+        None => true,
+        Some(ext) => DTS_EXTENSIONS.iter().any(|(js_ext, _)| *js_ext == ext),
+    };
+
+    // 静态资源, 不检查
+    if !is_js || path.is_file() {
+        return true;
+    }
+
+    if dts {
+        // ./foo.mjs -> ./foo.d.mts
+        if let Some(dts_path) = DTS_EXTENSIONS.iter().find_map(|(js_ext, dts_ext)| {
+            path_str.strip_suffix(js_ext).map(|base| format!("{base}{dts_ext}"))
+        }) {
+            if Path::new(&dts_path).is_file() {
+                return true;
+            }
+        }
+    }
+
+    // 无后缀: ./foo -> ./foo.js, ./foo/index.js
+    ext.is_none()
+        && DTS_EXTENSIONS.iter().any(|(js_ext, dts_ext)| {
+            let ext = if dts { dts_ext } else { js_ext };
+            Path::new(&format!("{path_str}{ext}")).is_file()
+                || path.join(format!("index{ext}")).is_file()
+        })
+}
+
+struct CollectSpecifiers {
+    bindings: Bindings,
+    specifiers: Vec<(String, Span)>,
+}
+
+impl CollectSpecifiers {
+    fn add(&mut self, src: &ast::Str) {
+        let specifier = src.value.to_atom_lossy().to_string();
+
+        if specifier.starts_with("./") || specifier.starts_with("../") {
+            self.specifiers.push((specifier, src.span));
+        }
+    }
+}
+
+impl Visit for CollectSpecifiers {
+    fn visit_import_decl(&mut self, n: &ast::ImportDecl) {
+        self.add(&n.src);
+    }
+
+    fn visit_export_all(&mut self, n: &ast::ExportAll) {
+        self.add(&n.src);
+    }
+
+    fn visit_named_export(&mut self, n: &ast::NamedExport) {
+        if let Some(src) = &n.src {
+            self.add(src);
+        }
+    }
+
+    // import('./foo'), require('./foo'), require.resolve('./foo'), import.meta.resolve('./foo')
+    fn visit_call_expr(&mut self, n: &ast::CallExpr) {
+        n.visit_children_with(self);
+
+        if self.bindings.is_specifier_call(&n.callee) {
+            if let Some(ast::Expr::Lit(ast::Lit::Str(src))) = n.args.first().map(|arg| &*arg.expr) {
+                self.add(src);
+            }
+        }
+    }
+
+    // .d.ts: import('./foo').A
+    fn visit_ts_import_type(&mut self, n: &ast::TsImportType) {
+        n.visit_children_with(self);
+        self.add(&n.arg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(path: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/verify").join(path)
+    }
+
+    fn broken_specifiers(file: &str, dts: bool) -> Vec<String> {
+        broken_imports(&fixture(file), dts)
+            .unwrap()
+            .into_iter()
+            .map(|(specifier, ..)| specifier)
+            .collect()
+    }
+
+    #[test]
+    fn test_broken_imports() {
+        assert_eq!(broken_specifiers("es/index.mjs", false), vec!["./missing.mjs", "./utils"]);
+        assert_eq!(broken_specifiers("es/index.d.mts", true), vec!["./missing.mjs"]);
+        assert_eq!(broken_specifiers("es/app.mjs", false), vec!["./button.mjs"]);
+    }
+}
@@ -0,0 +1,2 @@
+export { foo } from "@/utils";
+export { foo as bar } from "./utils";
@@ -0,0 +1,9 @@
+import type { ReactNode } from "react";
+
+export interface Config {
+	label: ReactNode;
+}
+
+export function button(config: Config): string {
+	return String(config.label);
+}
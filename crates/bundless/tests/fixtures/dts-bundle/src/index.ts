@@ -0,0 +1,8 @@
+import type { Options } from "@/options";
+
+export * from "./button";
+export { type Config as ListConfig, list } from "./list";
+
+export declare const options: Options;
+
+export default options;
@@ -0,0 +1,7 @@
+import type { ReactNode } from "react";
+
+export interface Config {
+	items: ReactNode[];
+}
+
+export const list = (config: Config): number => config.items.length;
@@ -0,0 +1,5 @@
+export type ButtonProps = {
+	type: "primary" | "default";
+};
+
+export const button = (props: ButtonProps): string => props.type;
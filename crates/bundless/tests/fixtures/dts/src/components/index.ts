@@ -0,0 +1,6 @@
+import type { Options } from "@/utils";
+
+export * from "./button.ts";
+
+export declare const options: Options;
+export declare const button: typeof import("./button");
@@ -0,0 +1,7 @@
+export function add(a: number, b: number) {
+	return a + b;
+}
+
+export const value = add(1, 2);
+
+export const typed: number = 1;
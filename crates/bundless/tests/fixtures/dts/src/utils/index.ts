@@ -0,0 +1,5 @@
+export const add = (a: number, b: number): number => a + b;
+
+export interface Options {
+	name: string;
+}
@@ -0,0 +1,4 @@
+import { debounce } from "lodash";
+import { add } from "./utils";
+
+export const run = debounce(() => add(1, 2), 100);
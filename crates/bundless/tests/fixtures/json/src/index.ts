@@ -0,0 +1,5 @@
+import data, { version } from "./data.json";
+
+const fs = require("fs");
+
+console.log(data, version, fs);
@@ -0,0 +1,3 @@
+export { Button } from "./Button";
+export { Card } from "./components";
+export { sum } from "./utils";
@@ -0,0 +1 @@
+export const config = await Promise.resolve({ name: "lecp" });
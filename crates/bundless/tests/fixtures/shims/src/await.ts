@@ -0,0 +1,4 @@
+export type Config = { name: string };
+
+const config: Config = await Promise.resolve({ name: "lecp" });
+console.log(config.name);
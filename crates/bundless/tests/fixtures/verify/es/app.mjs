@@ -0,0 +1,4 @@
+import { a } from "./a.mjs";
+import { Button } from "./button.mjs";
+
+export const App = () => <Button value={a} />;
@@ -0,0 +1,5 @@
+import { a } from "./a.mjs";
+
+export declare const b: typeof import("./missing.mjs").b;
+
+export { a };
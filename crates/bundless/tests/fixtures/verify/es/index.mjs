@@ -0,0 +1,10 @@
+import { a } from "./a.mjs";
+import { b } from "./missing.mjs";
+import "./style.css";
+import pkg from "pkg";
+
+export * from "./components";
+
+const utils = import("./utils");
+
+export { a, b, pkg, utils };
@@ -1,9 +1,28 @@
 /* auto-generated by NAPI-RS */
 /* eslint-disable */
+export declare function bundleDtsAsync(entries: Array<string>, options: Buffer): Promise<void>
+
 export declare function bundlessDtsAsync(file: string, options: Buffer): Promise<void>
 
-export declare function bundlessFileAsync(file: string, options: Buffer): Promise<void>
+export declare function bundlessFileAsync(file: string, options: Buffer, hooks?: JsBundlessHooks | undefined | null): Promise<void>
+
+export declare function bundlessFilesAsync(options: Buffer, hooks?: JsBundlessHooks | undefined | null): Promise<void>
 
-export declare function bundlessFilesAsync(options: Buffer): Promise<void>
+/**
+ * JS 构建钩子, 在 bundless worker 线程中同步调用 (不支持返回 Promise)
+ *
+ * 执行顺序: transform -> resolveId -> swc -> postTransform -> renderChunk -> 写入产物,
+ * 钩子抛出的错误会中断构建
+ */
+export interface JsBundlessHooks {
+  /** 重写导入路径, 返回 null / undefined 保持不变 */
+  resolveId?: (specifier: string, importer: string) => string | null | undefined
+  /** swc 转换前, 返回 false 跳过该文件, null / undefined 保持不变 */
+  transform?: (code: string, id: string) => string | false | null | undefined
+  /** swc 转换后 */
+  postTransform?: (code: string, id: string) => string | null | undefined
+  /** 写入产物前, fileName 为产物路径 */
+  renderChunk?: (code: string, fileName: string) => string | null | undefined
+}
 
 export declare function transformDtsAsync(file: string, options: Buffer): Promise<string>
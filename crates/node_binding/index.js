@@ -572,6 +572,7 @@ if (!nativeBinding) {
 }
 
 module.exports = nativeBinding
+module.exports.bundleDtsAsync = nativeBinding.bundleDtsAsync
 module.exports.bundlessDtsAsync = nativeBinding.bundlessDtsAsync
 module.exports.bundlessFileAsync = nativeBinding.bundlessFileAsync
 module.exports.bundlessFilesAsync = nativeBinding.bundlessFilesAsync
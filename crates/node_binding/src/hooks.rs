@@ -0,0 +1,104 @@
+use std::path::Path;
+use std::sync::mpsc;
+
+use lecp_bundless::BundlessHooks;
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi_derive::napi;
+
+/// JS 回调: (a: string, b: string) => Return
+type JsCallback<Return> =
+    ThreadsafeFunction<FnArgs<(String, String)>, Return, FnArgs<(String, String)>, Status, false>;
+
+/// JS 构建钩子, 在 bundless worker 线程中同步调用 (不支持返回 Promise)
+///
+/// 执行顺序: transform -> resolveId -> swc -> postTransform -> renderChunk -> 写入产物,
+/// 钩子抛出的错误会中断构建
+#[napi(object, object_to_js = false)]
+pub struct JsBundlessHooks {
+    /// 重写导入路径, 返回 null / undefined 保持不变
+    #[napi(ts_type = "(specifier: string, importer: string) => string | null | undefined")]
+    pub resolve_id: Option<JsCallback<Option<String>>>,
+    /// swc 转换前, 返回 false 跳过该文件, null / undefined 保持不变
+    #[napi(ts_type = "(code: string, id: string) => string | false | null | undefined")]
+    pub transform: Option<JsCallback<Option<Either<String, bool>>>>,
+    /// swc 转换后
+    #[napi(ts_type = "(code: string, id: string) => string | null | undefined")]
+    pub post_transform: Option<JsCallback<Option<String>>>,
+    /// 写入产物前, fileName 为产物路径
+    #[napi(ts_type = "(code: string, fileName: string) => string | null | undefined")]
+    pub render_chunk: Option<JsCallback<Option<String>>>,
+}
+
+/// 阻塞等待 JS 回调返回 (调用方不能是 JS 主线程)
+fn call_sync<Return: 'static + FromNapiValue + Send>(
+    callback: &JsCallback<Return>,
+    hook: &str,
+    a: String,
+    b: String,
+) -> anyhow::Result<Return> {
+    let (tx, rx) = mpsc::channel();
+
+    let status = callback.call_with_return_value(
+        (a, b).into(),
+        ThreadsafeFunctionCallMode::Blocking,
+        move |ret: Result<Return>, _env: Env| {
+            let _ = tx.send(ret);
+            Ok(())
+        },
+    );
+    if status != Status::Ok {
+        anyhow::bail!("failed to call {hook} hook: {status}");
+    }
+
+    rx.recv()
+        .map_err(|_| anyhow::anyhow!("{hook} hook returned no value"))?
+        .map_err(|e| anyhow::anyhow!("{hook} hook failed: {}", e.reason))
+}
+
+fn to_string(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+impl BundlessHooks for JsBundlessHooks {
+    fn resolve_id(&self, specifier: &str, importer: &Path) -> anyhow::Result<Option<String>> {
+        match &self.resolve_id {
+            Some(callback) => {
+                call_sync(callback, "resolveId", specifier.to_string(), to_string(importer))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn transform(&self, code: String, id: &Path) -> anyhow::Result<Option<String>> {
+        let Some(callback) = &self.transform else {
+            return Ok(Some(code));
+        };
+
+        match call_sync(callback, "transform", code.clone(), to_string(id))? {
+            Some(Either::A(code)) => Ok(Some(code)),
+            Some(Either::B(false)) => Ok(None),
+            Some(Either::B(true)) | None => Ok(Some(code)),
+        }
+    }
+
+    fn post_transform(&self, code: String, id: &Path) -> anyhow::Result<String> {
+        match &self.post_transform {
+            Some(callback) => {
+                Ok(call_sync(callback, "postTransform", code.clone(), to_string(id))?
+                    .unwrap_or(code))
+            }
+            None => Ok(code),
+        }
+    }
+
+    fn render_chunk(&self, code: String, out_path: &Path) -> anyhow::Result<String> {
+        match &self.render_chunk {
+            Some(callback) => {
+                Ok(call_sync(callback, "renderChunk", code.clone(), to_string(out_path))?
+                    .unwrap_or(code))
+            }
+            None => Ok(code),
+        }
+    }
+}
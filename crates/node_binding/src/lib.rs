@@ -1,11 +1,30 @@
+use std::sync::Arc;
+
 use lecp_bundless::{
-    BundlessOptions, bundless_dts_file, bundless_file, bundless_files, transform_dts_file,
+    BundlessBuilder, BundlessOptions, bundless_bundle_dts, bundless_dts_file, bundless_file,
+    bundless_files, transform_dts_file,
 };
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
+mod hooks;
+
+pub use crate::hooks::JsBundlessHooks;
+
 pub struct BundlessJsTask {
     options: Buffer,
+    hooks: Option<Arc<JsBundlessHooks>>,
+}
+
+// options + JS 钩子
+fn build_options(options: &Buffer, hooks: Option<Arc<JsBundlessHooks>>) -> Result<BundlessOptions> {
+    let options = serde_json::from_slice::<BundlessOptions>(options.as_ref())
+        .map_err(|e| Error::from_reason(format!("parse option error: {e}")))?;
+
+    Ok(match hooks {
+        Some(hooks) => BundlessBuilder::new(options).hooks(hooks).build(),
+        None => options,
+    })
 }
 
 #[napi]
@@ -14,10 +33,7 @@ impl Task for BundlessJsTask {
     type JsValue = ();
 
     fn compute(&mut self) -> Result<Self::Output> {
-        let options = match serde_json::from_slice::<BundlessOptions>(self.options.as_ref()) {
-            Ok(opts) => opts,
-            Err(e) => return Err(Error::from_reason(format!("parse option error: {e}"))),
-        };
+        let options = build_options(&self.options, self.hooks.clone())?;
 
         bundless_files(&options).map_err(|e| Error::from_reason(format!("build failed: {e}")))
     }
@@ -28,18 +44,44 @@ impl Task for BundlessJsTask {
 }
 
 #[napi]
-pub fn bundless_files_async(options: Buffer) -> AsyncTask<BundlessJsTask> {
-    AsyncTask::new(BundlessJsTask { options })
+pub fn bundless_files_async(
+    options: Buffer,
+    hooks: Option<JsBundlessHooks>,
+) -> AsyncTask<BundlessJsTask> {
+    AsyncTask::new(BundlessJsTask { options, hooks: hooks.map(Arc::new) })
+}
+
+/// 单文件构建, JS 钩子在 libuv 线程池中阻塞等待 (不能占用 tokio worker)
+pub struct BundlessFileJsTask {
+    file: String,
+    options: Buffer,
+    hooks: Option<Arc<JsBundlessHooks>>,
 }
 
 #[napi]
-pub async fn bundless_file_async(file: String, options: Buffer) -> Result<()> {
-    let options = match serde_json::from_slice::<BundlessOptions>(options.as_ref()) {
-        Ok(opts) => opts,
-        Err(e) => return Err(Error::from_reason(format!("parse option error: {e}"))),
-    };
+impl Task for BundlessFileJsTask {
+    type Output = ();
+    type JsValue = ();
 
-    bundless_file(&file, &options).map_err(|e| Error::from_reason(format!("build failed: {e}")))
+    fn compute(&mut self) -> Result<Self::Output> {
+        let options = build_options(&self.options, self.hooks.clone())?;
+
+        bundless_file(&self.file, &options)
+            .map_err(|e| Error::from_reason(format!("build failed: {e}")))
+    }
+
+    fn resolve(&mut self, _: Env, _output: Self::Output) -> Result<Self::JsValue> {
+        Ok(())
+    }
+}
+
+#[napi]
+pub fn bundless_file_async(
+    file: String,
+    options: Buffer,
+    hooks: Option<JsBundlessHooks>,
+) -> AsyncTask<BundlessFileJsTask> {
+    AsyncTask::new(BundlessFileJsTask { file, options, hooks: hooks.map(Arc::new) })
 }
 
 #[napi]
@@ -62,3 +104,11 @@ pub async fn transform_dts_async(file: String, options: Buffer) -> Result<String
     transform_dts_file(&file, &options)
         .map_err(|e| Error::from_reason(format!("build failed: {e}")))
 }
+
+#[napi]
+pub async fn bundle_dts_async(entries: Vec<String>, options: Buffer) -> Result<()> {
+    let options = build_options(&options, None)?;
+
+    bundless_bundle_dts(&entries, &options)
+        .map_err(|e| Error::from_reason(format!("build failed: {e}")))
+}
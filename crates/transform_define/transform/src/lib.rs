@@ -1,10 +1,170 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
 use serde_json::Value;
 use swc_core::common::DUMMY_SP;
+use swc_core::common::collections::AHashSet;
 use swc_core::ecma::ast::{
-    ArrayLit, Expr, ExprOrSpread, Ident, ImportSpecifier, KeyValueProp, Lit, MemberExpr,
-    MemberProp, ObjectLit, Pass, Prop, PropName, PropOrSpread, UnaryOp,
+    ArrayLit, BreakStmt, ContinueStmt, ExportSpecifier, Expr, ExprOrSpread, Id, Ident,
+    ImportSpecifier, JSXAttrName, JSXElementName, KeyValueProp, LabeledStmt, Lit, MemberExpr,
+    MemberProp, MetaPropExpr, MetaPropKind, ObjectLit, Pass, Program, Prop, PropName, PropOrSpread,
+    TsExprWithTypeArgs, TsInterfaceDecl, TsType, TsTypeAliasDecl, TsTypeParamDecl, UnaryExpr,
+    UnaryOp,
+};
+use swc_core::ecma::utils::collect_decls;
+use swc_core::ecma::visit::{
+    Visit, VisitMut, VisitMutWith, VisitWith, noop_visit_type, visit_mut_pass,
 };
-use swc_core::ecma::visit::{VisitMut, VisitMutWith, visit_mut_pass};
+
+/// Number of replaced references per define key
+#[derive(Debug, Default, Clone)]
+pub struct DefineStats {
+    pub hits: HashMap<String, usize>,
+}
+
+impl DefineStats {
+    pub fn record(&mut self, key: &str) {
+        *self.hits.entry(key.to_string()).or_default() += 1;
+    }
+
+    /// Define keys that never matched a reference
+    pub fn unused_keys<'a>(&self, options: &'a Value) -> Vec<&'a str> {
+        let mut keys: Vec<&str> = options
+            .as_object()
+            .map(|obj| {
+                obj.keys().map(String::as_str).filter(|k| !self.hits.contains_key(*k)).collect()
+            })
+            .unwrap_or_default();
+        keys.sort_unstable();
+        keys
+    }
+}
+
+/// Overlapping define keys, e.g. `"process.env.NODE_ENV"` and `{ "process": { "env": {...} } }`.
+/// Returns `(dotted key, shadowed key)` pairs, the dotted key takes precedence.
+pub fn conflicting_keys(options: &Value) -> Vec<(String, String)> {
+    let Some(obj) = options.as_object() else {
+        return vec![];
+    };
+
+    let mut conflicts = vec![];
+    for key in obj.keys().filter(|k| !k.starts_with("typeof ")) {
+        let segments: Vec<&str> = key.split('.').collect();
+
+        for i in 1..segments.len() {
+            let prefix = segments[..i].join(".");
+            if obj.get(&prefix).is_some_and(Value::is_object) {
+                conflicts.push((key.clone(), prefix));
+            }
+        }
+    }
+
+    conflicts.sort();
+    conflicts
+}
+
+/// Count references to define `keys` into `stats` without changing the program,
+/// for defines replaced by swc `jsc.transform.optimizer.globals`.
+///
+/// Must run after `resolver`: names bound in the program (`const __DEV__ = 1`, params)
+/// are not references to the define.
+pub fn collect_stats(keys: Vec<String>, stats: Arc<Mutex<DefineStats>>) -> impl Pass {
+    DefineUsage { keys: keys.into_iter().collect(), stats }
+}
+
+struct DefineUsage {
+    keys: HashSet<String>,
+    stats: Arc<Mutex<DefineStats>>,
+}
+
+impl Pass for DefineUsage {
+    fn process(&mut self, program: &mut Program) {
+        if self.keys.is_empty() {
+            return;
+        }
+
+        let decls = collect_decls(&*program);
+        program.visit_with(&mut DefineUsageVisitor { keys: &self.keys, decls, stats: &self.stats });
+    }
+}
+
+struct DefineUsageVisitor<'a> {
+    keys: &'a HashSet<String>,
+    decls: AHashSet<Id>,
+    stats: &'a Mutex<DefineStats>,
+}
+
+impl DefineUsageVisitor<'_> {
+    // 未在程序内声明, 即全局变量
+    fn is_global(&self, i: &Ident) -> bool {
+        !self.decls.contains(&i.to_id())
+    }
+
+    fn record(&self, key: &str) -> bool {
+        let is_define = self.keys.contains(key);
+        if is_define {
+            self.stats.lock().unwrap().record(key);
+        }
+        is_define
+    }
+}
+
+impl Visit for DefineUsageVisitor<'_> {
+    noop_visit_type!();
+
+    fn visit_expr(&mut self, e: &Expr) {
+        match e {
+            Expr::Ident(i) if self.is_global(i) => {
+                self.record(&i.sym);
+            }
+            // process.env.NODE_ENV
+            Expr::Member(member) => {
+                if let Some((root, key)) = member_key(member) {
+                    if self.is_global(root) && self.record(&key) {
+                        return;
+                    }
+                }
+            }
+            // typeof window
+            Expr::Unary(UnaryExpr { op: UnaryOp::TypeOf, arg, .. }) => {
+                if let Expr::Ident(i) = &**arg {
+                    if self.is_global(i) && self.record(&format!("typeof {}", i.sym)) {
+                        return;
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        e.visit_children_with(self);
+    }
+
+    // { __DEV__ }
+    fn visit_prop(&mut self, p: &Prop) {
+        if let Prop::Shorthand(i) = p {
+            if self.is_global(i) {
+                self.record(&i.sym);
+            }
+        }
+
+        p.visit_children_with(self);
+    }
+}
+
+// process.env.NODE_ENV -> (process, "process.env.NODE_ENV")
+fn member_key(e: &MemberExpr) -> Option<(&Ident, String)> {
+    let MemberProp::Ident(prop) = &e.prop else {
+        return None;
+    };
+
+    let (root, obj) = match &*e.obj {
+        Expr::Ident(i) => (i, i.sym.to_string()),
+        Expr::Member(obj) => member_key(obj)?,
+        _ => return None,
+    };
+
+    Some((root, format!("{obj}.{}", prop.sym)))
+}
 
 #[derive(Default)]
 pub struct TransformDefine {
@@ -35,6 +195,41 @@ impl VisitMut for TransformDefine {
         self.in_import_specifier = old_in_import_specifier;
     }
 
+    // 以下位置的 ident 不是值引用, 不做替换
+
+    // export { STR }
+    fn visit_mut_export_specifier(&mut self, _: &mut ExportSpecifier) {}
+
+    // <STR.Item></STR.Item>
+    fn visit_mut_jsx_element_name(&mut self, _: &mut JSXElementName) {}
+
+    // <div STR={STR} />
+    fn visit_mut_jsx_attr_name(&mut self, _: &mut JSXAttrName) {}
+
+    // STR: for (;;) { break STR; }
+    fn visit_mut_labeled_stmt(&mut self, node: &mut LabeledStmt) {
+        node.body.visit_mut_with(self);
+    }
+
+    fn visit_mut_break_stmt(&mut self, _: &mut BreakStmt) {}
+
+    fn visit_mut_continue_stmt(&mut self, _: &mut ContinueStmt) {}
+
+    // const a: STR = STR; (ts 类型在 strip 前)
+    fn visit_mut_ts_type(&mut self, _: &mut TsType) {}
+
+    // function fn<STR>() {}
+    fn visit_mut_ts_type_param_decl(&mut self, _: &mut TsTypeParamDecl) {}
+
+    // interface STR {}
+    fn visit_mut_ts_interface_decl(&mut self, _: &mut TsInterfaceDecl) {}
+
+    // type STR = string;
+    fn visit_mut_ts_type_alias_decl(&mut self, _: &mut TsTypeAliasDecl) {}
+
+    // class A implements STR {}
+    fn visit_mut_ts_expr_with_type_args(&mut self, _: &mut TsExprWithTypeArgs) {}
+
     // scene: const x = { version: VERSION };
     fn visit_mut_ident(&mut self, ident: &mut Ident) {
         ident.visit_mut_children_with(self);
@@ -116,6 +311,10 @@ fn get_node_path(e: MemberExpr, paths: &mut Vec<String>) -> &mut Vec<String> {
         Expr::Member(e1) => {
             get_node_path(e1, paths);
         }
+        // import.meta.env.X -> ["import.meta", "env", "X"]
+        Expr::MetaProp(MetaPropExpr { kind: MetaPropKind::ImportMeta, .. }) => {
+            paths.insert(0, "import.meta".to_string());
+        }
         _ => {}
     }
 
@@ -157,6 +356,7 @@ fn create_expr(value: Value) -> Option<Expr> {
 
 #[cfg(test)]
 mod tests {
+    use swc_core::ecma::parser::{EsSyntax, Syntax, TsSyntax};
     use swc_core::ecma::transforms::testing::test_inline;
 
     use super::*;
@@ -296,6 +496,35 @@ mod tests {
     "# // Output codes after transformed with plugin
     );
 
+    test_inline!(
+        Default::default(),
+        |_| transform(
+            serde_json::from_str(
+                r#"
+            {
+                "import.meta.env.VITE_API": "/api",
+                "import.meta.env": { "MODE": "production", "VITE_API": "/api" }
+            }"#
+            )
+            .unwrap()
+        ),
+        fn_import_meta_env,
+        r#"
+        fetch(import.meta.env.VITE_API);
+        console.log(import.meta.env);
+        console.log(import.meta.env.NOT_DEF);
+        console.log(import.meta.url);
+        export {}
+    "#, // Input codes,
+        r#"
+        fetch("/api");
+        console.log({ "MODE": "production", "VITE_API": "/api" });
+        console.log({ "MODE": "production", "VITE_API": "/api" }.NOT_DEF);
+        console.log(import.meta.url);
+        export {}
+    "# // Output codes after transformed with plugin
+    );
+
     // 'process.env.NODE_ENV 被正确转化-对象参数
     test_inline!(
         Default::default(),
@@ -350,4 +579,179 @@ mod tests {
         if ({}) console.log(true);
     "# // Output codes after transformed with plugin
     );
+
+    test_inline!(
+        Syntax::Es(EsSyntax { jsx: true, ..Default::default() }),
+        |_| transform(
+            serde_json::from_str(
+                r#"
+            {
+                "STR": "string"
+            }"#
+            )
+            .unwrap()
+        ),
+        fn_jsx,
+        r#"
+        <STR STR={STR}>{STR}</STR>;
+        <STR.Item />;
+    "#, // Input codes,
+        r#"
+        <STR STR={"string"}>{"string"}</STR>;
+        <STR.Item />;
+    "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Syntax::Typescript(TsSyntax { tsx: true, ..Default::default() }),
+        |_| transform(
+            serde_json::from_str(
+                r#"
+            {
+                "STR": "string"
+            }"#
+            )
+            .unwrap()
+        ),
+        fn_ts_type,
+        r#"
+        interface STR { a: STR }
+        type T = STR | typeof STR;
+        function fn<STR>(a: STR): STR { return a; }
+        class A implements STR {}
+        const a: STR = STR as STR;
+    "#, // Input codes,
+        r#"
+        interface STR { a: STR }
+        type T = STR | typeof STR;
+        function fn<STR>(a: STR): STR { return a; }
+        class A implements STR {}
+        const a: STR = "string" as STR;
+    "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Default::default(),
+        |_| transform(
+            serde_json::from_str(
+                r#"
+            {
+                "STR": "string"
+            }"#
+            )
+            .unwrap()
+        ),
+        fn_label,
+        r#"
+        STR: for (;;) {
+            if (STR) break STR;
+            continue STR;
+        }
+    "#, // Input codes,
+        r#"
+        STR: for (;;) {
+            if ("string") break STR;
+            continue STR;
+        }
+    "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Default::default(),
+        |_| transform(
+            serde_json::from_str(
+                r#"
+            {
+                "STR": "string"
+            }"#
+            )
+            .unwrap()
+        ),
+        fn_export_specifier,
+        r#"
+        export { STR };
+        export { STR as default };
+        export { default as STR } from "pkg1";
+        console.log(STR);
+    "#, // Input codes,
+        r#"
+        export { STR };
+        export { STR as default };
+        export { default as STR } from "pkg1";
+        console.log("string");
+    "# // Output codes after transformed with plugin
+    );
+
+    #[test]
+    fn test_conflicting_keys() {
+        let options = serde_json::json!({
+            "process.env.NODE_ENV": "production",
+            "process": { "env": { "NODE_ENV": "development" } },
+            "process.env": { "DEBUG": false },
+            "typeof window": "object",
+            "VERSION": "1.0.0"
+        });
+
+        assert_eq!(
+            conflicting_keys(&options),
+            vec![
+                ("process.env".to_string(), "process".to_string()),
+                ("process.env.NODE_ENV".to_string(), "process".to_string()),
+                ("process.env.NODE_ENV".to_string(), "process.env".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unused_keys() {
+        let options = serde_json::json!({
+            "VERSION": "1.0.0",
+            "process.env.NODE_ENV": "production",
+            "typeof window": "object"
+        });
+
+        let mut stats = DefineStats::default();
+        stats.record("VERSION");
+        stats.record("typeof window");
+
+        assert_eq!(stats.unused_keys(&options), vec!["process.env.NODE_ENV"]);
+    }
+
+    #[test]
+    fn test_collect_stats() {
+        use swc_core::common::Mark;
+        use swc_core::ecma::transforms::base::resolver;
+        use swc_core::ecma::transforms::testing::Tester;
+
+        let keys = ["__DEV__", "process.env.NODE_ENV", "typeof window", "VERSION", "UNUSED"]
+            .map(String::from)
+            .to_vec();
+        let stats = Arc::new(Mutex::new(DefineStats::default()));
+
+        Tester::run(|tester| {
+            let module = tester.parse_module(
+                "input.js",
+                r#"
+                if (__DEV__ && process.env.NODE_ENV !== "production") {}
+                console.log(typeof window, { __DEV__ });
+                function fn(VERSION) { return VERSION; }
+                const UNUSED = 1;
+                console.log(UNUSED);
+                "#,
+            )?;
+
+            let mut program = Program::Module(module);
+            program.mutate(resolver(Mark::new(), Mark::new(), false));
+            program.mutate(collect_stats(keys.clone(), stats.clone()));
+            Ok(())
+        });
+
+        let stats = stats.lock().unwrap();
+        assert_eq!(stats.hits["__DEV__"], 2);
+        assert_eq!(stats.hits["process.env.NODE_ENV"], 1);
+        assert_eq!(stats.hits["typeof window"], 1);
+        // 局部绑定 (参数, 变量) 不计入
+        assert!(!stats.hits.contains_key("VERSION"));
+        assert!(!stats.hits.contains_key("UNUSED"));
+    }
 }
@@ -1,11 +1,17 @@
+pub mod specifier;
+
 use core::str;
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
 use path_absolutize::*;
+use regex::Regex;
 use serde::Deserialize;
 use swc_core::ecma::ast::{self, Pass};
-use swc_core::ecma::visit::{VisitMut, VisitMutWith, noop_visit_mut_type, visit_mut_pass};
+use swc_core::ecma::visit::{VisitMut, VisitMutWith, visit_mut_pass};
+
+use crate::specifier::{Bindings, is_url_with_import_meta};
 
 #[derive(Clone, Debug, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -27,44 +33,333 @@ pub struct Config {
     /// Current file directory (absolute)
     #[serde(default)]
     pub current_dir: Option<String>,
+
+    /// Source extensions tried (in order) to resolve extensionless and directory imports
+    /// against `current_dir`, e.g. `[".ts", ".tsx", ".js"]`. Empty disables resolving.
+    ///
+    /// - `./utils` -> `./utils.ts` -> `./utils.js`
+    /// - `./components` -> `./components/index.tsx` -> `./components/index.js`
+    #[serde(default)]
+    pub resolve_extensions: Vec<String>,
+
+    /// Alias rules, the first matching rule wins (see [AliasRule])
+    #[serde(default)]
+    pub alias: Vec<AliasRule>,
 }
 
-fn replace_extension(src: &ast::Str, config: &Config) -> Option<ast::Str> {
-    let path = &src.value.to_atom_lossy().to_string();
+/// - `"@": ["/project/src"]`: `@`, `@/utils` -> `./utils`
+/// - `"lodash$": ["lodash-es"]`: 仅完全匹配 `lodash`, 不影响 `lodash/fp`
+/// - `"/^@(\\w+)\\//": ["/project/packages/$1/src/"]`: 正则, 支持 `$1` 引用
+///
+/// replacements 为绝对路径时, 依次检查磁盘上是否存在(含 `resolve_extensions`),
+/// 取第一个存在的并输出相对 `current_dir` 的路径; 否则视为包名直接替换.
+#[derive(Clone, Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AliasRule {
+    pub find: String,
+    pub replacements: Vec<String>,
+}
 
-    // Only handle relative paths starting with ./ or ../
-    if !path.starts_with("./") && !path.starts_with("../") {
+impl AliasRule {
+    fn regex(&self) -> Option<Result<Regex, regex::Error>> {
+        let pattern = self.find.strip_prefix('/')?.strip_suffix('/')?;
+        (!pattern.is_empty()).then(|| Regex::new(pattern))
+    }
+
+    /// 校验正则 key
+    pub fn validate(&self) -> Result<(), String> {
+        match self.regex() {
+            Some(Err(e)) => Err(format!("invalid alias {}: {e}", self.find)),
+            _ => Ok(()),
+        }
+    }
+}
+
+enum AliasPattern {
+    Exact(String),
+    Prefix(String),
+    Regex(Regex),
+}
+
+struct Alias {
+    pattern: AliasPattern,
+    replacements: Vec<String>,
+}
+
+impl Alias {
+    // 无效的正则忽略 (使用前应先 `AliasRule::validate`)
+    fn new(rule: &AliasRule) -> Option<Self> {
+        let pattern = match rule.regex() {
+            Some(regex) => AliasPattern::Regex(regex.ok()?),
+            None => match rule.find.strip_suffix('$') {
+                Some(exact) => AliasPattern::Exact(exact.to_string()),
+                None => AliasPattern::Prefix(rule.find.trim_end_matches('/').to_string()),
+            },
+        };
+
+        Some(Self { pattern, replacements: rule.replacements.clone() })
+    }
+
+    // 匹配后的候选 specifier (未经磁盘检查)
+    fn candidates(&self, specifier: &str) -> Option<Vec<String>> {
+        match &self.pattern {
+            AliasPattern::Exact(find) => (specifier == find).then(|| self.replacements.clone()),
+            AliasPattern::Prefix(find) => {
+                let rest = specifier.strip_prefix(find.as_str())?;
+                if !rest.is_empty() && !rest.starts_with('/') {
+                    return None;
+                }
+
+                let candidates = self
+                    .replacements
+                    .iter()
+                    .map(|replacement| format!("{}{rest}", replacement.trim_end_matches('/')))
+                    .collect();
+                Some(candidates)
+            }
+            AliasPattern::Regex(regex) => {
+                if !regex.is_match(specifier) {
+                    return None;
+                }
+
+                let candidates = self
+                    .replacements
+                    .iter()
+                    .map(|replacement| {
+                        regex.replacen(specifier, 1, replacement.as_str()).to_string()
+                    })
+                    .collect();
+                Some(candidates)
+            }
+        }
+    }
+}
+
+fn exists_on_disk(path: &Path, config: &Config) -> bool {
+    if path.exists() {
+        return true;
+    }
+
+    let path_str = path.to_string_lossy();
+    config.resolve_extensions.iter().any(|ext| {
+        Path::new(&format!("{path_str}{ext}")).is_file()
+            || path.join(format!("index{ext}")).is_file()
+    })
+}
+
+/// Relative import specifier from `from_dir` to `target`, always `/` separated
+/// - `/project/src/utils`, `/project/src/components` -> `../utils`
+fn to_relative_specifier(target: &Path, from_dir: &Path) -> Option<String> {
+    let rel_path = pathdiff::diff_paths(target, from_dir)?;
+    let specifier = rel_path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    if specifier.starts_with("..") { Some(specifier) } else { Some(format!("./{specifier}")) }
+}
+
+/// Rewrite an aliased specifier
+/// - 路径: 相对 `current_dir` 的路径
+/// - 包名: 直接替换
+fn resolve_alias(specifier: &str, alias: &[Alias], config: &Config) -> Option<String> {
+    let candidates = alias.iter().find_map(|alias| alias.candidates(specifier))?;
+
+    let is_path = |candidate: &String| Path::new(candidate).is_absolute();
+
+    let target = candidates
+        .iter()
+        .find(|candidate| is_path(candidate) && exists_on_disk(Path::new(candidate), config))
+        .or_else(|| candidates.first())?;
+
+    if !is_path(target) {
+        return Some(target.clone());
+    }
+
+    let current_dir = PathBuf::from(config.current_dir.as_ref()?);
+    let target = Path::new(target).absolutize().ok()?;
+    let mut relative = to_relative_specifier(&target, &current_dir)?;
+
+    // 保留目录写法 `@/components/` -> `./components/`
+    if specifier.ends_with('/') && !relative.ends_with('/') {
+        relative.push('/');
+    }
+
+    Some(relative)
+}
+
+// 源文件后缀 -> 产物后缀 (jsx, tsx 固定输出 .js)
+fn source_to_js_extension(ext: &str) -> &'static str {
+    match ext {
+        ".mts" | ".mjs" => ".mjs",
+        ".cts" | ".cjs" => ".cjs",
+        _ => ".js",
+    }
+}
+
+fn map_extension<'a>(ext: &'a str, config: &'a Config) -> &'a str {
+    if matches!(ext, ".tsx" | ".jsx") {
+        return ".js";
+    }
+
+    let js_ext = source_to_js_extension(ext);
+    config.extensions.get(js_ext).map_or(js_ext, String::as_str)
+}
+
+/// Resolve an extensionless or directory specifier against the filesystem
+/// `./utils` -> `./utils.js`, `./components` -> `./components/index.js`
+fn resolve_specifier(path: &str, config: &Config) -> Option<String> {
+    if config.resolve_extensions.is_empty() {
         return None;
     }
 
-    // Check if the resolved path is within source_dir
-    if let (Some(source_dir), Some(current_dir)) = (&config.source_dir, &config.current_dir) {
-        let import_path = Path::new(current_dir).join(path);
-        let absolute_path = import_path.absolutize().ok()?;
+    let current_dir = config.current_dir.as_ref()?;
+    let base = Path::new(current_dir).join(path);
+
+    // 已指向存在的文件(./style.css, ./a.js), 无需处理
+    if base.is_file() {
+        return None;
+    }
 
-        if !absolute_path.starts_with(source_dir) {
-            return None;
+    let base_str = base.to_string_lossy();
+    if let Some(ext) = config
+        .resolve_extensions
+        .iter()
+        .find(|ext| Path::new(&format!("{base_str}{ext}")).is_file())
+    {
+        return Some(format!("{path}{}", map_extension(ext, config)));
+    }
+
+    if base.is_dir() {
+        if let Some(ext) =
+            config.resolve_extensions.iter().find(|ext| base.join(format!("index{ext}")).is_file())
+        {
+            let dir = path.trim_end_matches('/');
+            return Some(format!("{dir}/index{}", map_extension(ext, config)));
         }
     }
 
+    None
+}
+
+// Only handle relative paths (./ or ../) within source_dir
+fn is_source_import(path: &str, config: &Config) -> bool {
+    if !path.starts_with("./") && !path.starts_with("../") {
+        return false;
+    }
+
+    match (&config.source_dir, &config.current_dir) {
+        (Some(source_dir), Some(current_dir)) => Path::new(current_dir)
+            .join(path)
+            .absolutize()
+            .is_ok_and(|absolute_path| absolute_path.starts_with(source_dir)),
+        _ => true,
+    }
+}
+
+fn replace_extension(path: &str, config: &Config) -> Option<String> {
+    if !is_source_import(path, config) {
+        return None;
+    }
+
     // Find and replace extension
-    config.extensions.iter().find(|(key, _)| path.ends_with(key.as_str())).and_then(|(key, ext)| {
-        path.strip_suffix(key.as_str()).map(|name| format!("{name}{ext}").into())
-    })
+    config
+        .extensions
+        .iter()
+        .find(|(key, _)| path.ends_with(key.as_str()))
+        .and_then(|(key, ext)| path.strip_suffix(key.as_str()).map(|name| format!("{name}{ext}")))
+        .or_else(|| resolve_specifier(path, config))
+}
+
+/// 已补全后缀的 specifier (产物后缀), 后续 passes 不再替换后缀
+///
+/// `./Button` -> `./Button.js` (Button.tsx 固定输出 .js), 不应再被 `.js` -> `.mjs` 替换
+pub type ResolvedSpecifiers = Arc<Mutex<HashSet<String>>>;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Rewrite {
+    /// alias -> extensions
+    All,
+    /// 只处理 alias
+    Alias,
+    /// 只补全无后缀 / 目录导入
+    Resolve,
+}
+
+// alias -> extensions
+fn rewrite_specifier(
+    src: &ast::Str,
+    alias: &[Alias],
+    config: &Config,
+    rewrite: Rewrite,
+    resolved: Option<&ResolvedSpecifiers>,
+) -> Option<ast::Str> {
+    let specifier = src.value.to_atom_lossy().to_string();
+
+    let aliased = resolve_alias(&specifier, alias, config);
+    if rewrite == Rewrite::Alias {
+        return aliased.map(Into::into);
+    }
+
+    let path = aliased.as_deref().unwrap_or(&specifier);
+
+    if resolved.is_some_and(|resolved| resolved.lock().unwrap().contains(path)) {
+        return aliased.map(Into::into);
+    }
+
+    let replaced = match rewrite {
+        Rewrite::Resolve => {
+            let replaced =
+                if is_source_import(path, config) { resolve_specifier(path, config) } else { None };
+            if let (Some(replaced), Some(resolved)) = (&replaced, resolved) {
+                resolved.lock().unwrap().insert(replaced.clone());
+            }
+            replaced
+        }
+        _ => replace_extension(path, config),
+    };
+
+    replaced.or(aliased).map(Into::into)
 }
 
 struct RewriteImportingExtensions {
     config: Config,
+    alias: Vec<Alias>,
+    bindings: Bindings,
+    rewrite: Rewrite,
+    resolved: Option<ResolvedSpecifiers>,
+}
+
+impl RewriteImportingExtensions {
+    fn new(config: Config, rewrite: Rewrite, resolved: Option<ResolvedSpecifiers>) -> Self {
+        // 只补全后缀时不处理 alias
+        let alias = match rewrite {
+            Rewrite::Resolve => vec![],
+            _ => config.alias.iter().filter_map(Alias::new).collect(),
+        };
+
+        Self { config, alias, bindings: Default::default(), rewrite, resolved }
+    }
+
+    fn rewrite(&self, src: &ast::Str) -> Option<ast::Str> {
+        rewrite_specifier(src, &self.alias, &self.config, self.rewrite, self.resolved.as_ref())
+    }
 }
 
 impl VisitMut for RewriteImportingExtensions {
-    noop_visit_mut_type!();
+    // 不使用 noop_visit_mut_type!(), 需要访问 .d.ts 中的 import('./foo').A
+
+    fn visit_mut_program(&mut self, n: &mut ast::Program) {
+        self.bindings = Bindings::new(n);
+        n.visit_mut_children_with(self);
+    }
 
     // import './foo.ts'
     fn visit_mut_import_decl(&mut self, n: &mut ast::ImportDecl) {
         n.visit_mut_children_with(self);
 
-        if let Some(replaced) = replace_extension(&n.src, &self.config) {
+        if let Some(replaced) = self.rewrite(&n.src) {
             n.src = Box::new(replaced);
         }
     }
@@ -73,7 +368,7 @@ impl VisitMut for RewriteImportingExtensions {
     fn visit_mut_export_all(&mut self, n: &mut ast::ExportAll) {
         n.visit_mut_children_with(self);
 
-        if let Some(replaced) = replace_extension(&n.src, &self.config) {
+        if let Some(replaced) = self.rewrite(&n.src) {
             n.src = Box::new(replaced);
         }
     }
@@ -83,20 +378,45 @@ impl VisitMut for RewriteImportingExtensions {
         n.visit_mut_children_with(self);
 
         if let Some(src) = &n.src {
-            if let Some(replaced) = replace_extension(src, &self.config) {
+            if let Some(replaced) = self.rewrite(src) {
                 n.src = Some(Box::new(replaced));
             }
         }
     }
 
     // import('./foo.ts')
+    // require('./foo.ts'), require.resolve('./foo.ts'), import.meta.resolve('./foo.ts')
     fn visit_mut_call_expr(&mut self, n: &mut ast::CallExpr) {
         n.visit_mut_children_with(self);
 
-        if n.callee.is_import() && !n.args.is_empty() {
+        if self.bindings.is_specifier_call(&n.callee) && !n.args.is_empty() {
             if let Some(value) = n.args.first_mut() {
                 if let ast::Expr::Lit(ast::Lit::Str(src)) = value.expr.as_ref() {
-                    if let Some(replaced) = replace_extension(src, &self.config) {
+                    if let Some(replaced) = self.rewrite(src) {
+                        value.expr = Box::new(ast::Expr::Lit(ast::Lit::Str(replaced)));
+                    }
+                }
+            }
+        }
+    }
+
+    // .d.ts: type A = import('./foo.ts').A
+    fn visit_mut_ts_import_type(&mut self, n: &mut ast::TsImportType) {
+        n.visit_mut_children_with(self);
+
+        if let Some(replaced) = self.rewrite(&n.arg) {
+            n.arg = replaced;
+        }
+    }
+
+    // new URL('./foo.ts', import.meta.url)
+    fn visit_mut_new_expr(&mut self, n: &mut ast::NewExpr) {
+        n.visit_mut_children_with(self);
+
+        if is_url_with_import_meta(n) {
+            if let Some(value) = n.args.as_mut().and_then(|args| args.first_mut()) {
+                if let ast::Expr::Lit(ast::Lit::Str(src)) = value.expr.as_ref() {
+                    if let Some(replaced) = self.rewrite(src) {
                         value.expr = Box::new(ast::Expr::Lit(ast::Lit::Str(replaced)));
                     }
                 }
@@ -106,7 +426,23 @@ impl VisitMut for RewriteImportingExtensions {
 }
 
 pub fn transform(config: Config) -> impl Pass {
-    visit_mut_pass(RewriteImportingExtensions { config })
+    visit_mut_pass(RewriteImportingExtensions::new(config, Rewrite::All, None))
+}
+
+/// 同 [transform], 跳过 [resolve_extensions] 已补全后缀的 specifier
+pub fn transform_with_resolved(config: Config, resolved: ResolvedSpecifiers) -> impl Pass {
+    visit_mut_pass(RewriteImportingExtensions::new(config, Rewrite::All, Some(resolved)))
+}
+
+/// 只处理 `config.alias`, 不修改后缀 (`extensions`, `resolve_extensions` 仅用于检查 alias 目标是否存在)
+pub fn alias(config: Config) -> impl Pass {
+    visit_mut_pass(RewriteImportingExtensions::new(config, Rewrite::Alias, None))
+}
+
+/// 只补全无后缀 / 目录导入 (`config.resolve_extensions`), 输出产物后缀 (`config.extensions`),
+/// 补全结果记录到 `resolved`
+pub fn resolve_extensions(config: Config, resolved: ResolvedSpecifiers) -> impl Pass {
+    visit_mut_pass(RewriteImportingExtensions::new(config, Rewrite::Resolve, Some(resolved)))
 }
 
 #[cfg(test)]
@@ -265,4 +601,236 @@ mod tests {
         // import c from "./c.js";
     "# // Output codes after transformed with plugin
     );
+
+    test_inline!(
+        Default::default(),
+        |_| transform(Config {
+            extensions: HashMap::from([
+                (".js".to_string(), ".mjs".to_string()),
+                (".mjs".to_string(), ".mjs".to_string()),
+                (".cjs".to_string(), ".mjs".to_string()),
+            ]),
+            current_dir: Some(format!("{}/tests/fixtures/resolve", env!("CARGO_MANIFEST_DIR"))),
+            resolve_extensions: [".ts", ".tsx", ".mts", ".cts", ".js", ".jsx", ".mjs", ".cjs"]
+                .map(String::from)
+                .to_vec(),
+            ..Default::default()
+        }),
+        fn_resolve_extensionless,
+        r#"
+        import a from "./utils";
+        import b from "./components";
+        import c from "./components/";
+        import d from "./helper.service";
+        import e from "./Button";
+        import "./style.css";
+        import f from "./not-found";
+        import g from "pkg";
+        const h = import("./utils");
+    "#, // Input codes,
+        r#"
+        import a from "./utils.mjs";
+        import b from "./components/index.js";
+        import c from "./components/index.js";
+        import d from "./helper.service.mjs";
+        import e from "./Button.js";
+        import "./style.css";
+        import f from "./not-found";
+        import g from "pkg";
+        const h = import("./utils.mjs");
+    "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Default::default(),
+        |_| transform(
+            serde_json::from_str(
+                r#"
+            {
+                "extensions": {
+                    ".js": ".cjs",
+                    ".mjs": ".cjs"
+                }
+            }"#
+            )
+            .unwrap()
+        ),
+        fn_require_esm2cjs,
+        r#"
+        const a = require("./foo.js");
+        const b = require.resolve("./foo.mjs");
+        const c = import.meta.resolve("./foo.js");
+        const d = new URL("./worker.js", import.meta.url);
+        const e = new URL("./foo.js", "https://example.com");
+        const f = load("./foo.js");
+    "#, // Input codes,
+        r#"
+        const a = require("./foo.cjs");
+        const b = require.resolve("./foo.cjs");
+        const c = import.meta.resolve("./foo.cjs");
+        const d = new URL("./worker.cjs", import.meta.url);
+        const e = new URL("./foo.js", "https://example.com");
+        const f = load("./foo.js");
+    "# // Output codes after transformed with plugin
+    );
+
+    // 用户自定义的 require 不是 CommonJS require
+    test_inline!(
+        Default::default(),
+        |_| transform(
+            serde_json::from_str(
+                r#"
+            {
+                "extensions": {
+                    ".js": ".cjs"
+                }
+            }"#
+            )
+            .unwrap()
+        ),
+        fn_require_local,
+        r#"
+        function require(path) {}
+        const a = require("./foo.js");
+        const b = require.resolve("./foo.js");
+        const c = import("./foo.js");
+    "#, // Input codes,
+        r#"
+        function require(path) {}
+        const a = require("./foo.js");
+        const b = require.resolve("./foo.js");
+        const c = import("./foo.cjs");
+    "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Default::default(),
+        |_| {
+            let fixtures = format!("{}/tests/fixtures/resolve", env!("CARGO_MANIFEST_DIR"));
+            transform(Config {
+                extensions: HashMap::from([(".js".to_string(), ".mjs".to_string())]),
+                current_dir: Some(format!("{fixtures}/components")),
+                resolve_extensions: [".ts", ".tsx", ".mts", ".js"].map(String::from).to_vec(),
+                alias: vec![
+                    AliasRule {
+                        find: "@".to_string(),
+                        replacements: vec![format!("{fixtures}/not-found"), fixtures.clone()],
+                    },
+                    AliasRule {
+                        find: "lodash$".to_string(),
+                        replacements: vec!["lodash-es".to_string()],
+                    },
+                    AliasRule {
+                        find: "/^~(\\w+)$/".to_string(),
+                        replacements: vec![format!("{fixtures}/$1")],
+                    },
+                ],
+                ..Default::default()
+            })
+        },
+        fn_alias,
+        r#"
+        import a from "@/utils";
+        import b from "lodash";
+        import c from "lodash/fp";
+        import d from "~Button";
+        import e from "@scope/pkg";
+        export * from "@/helper.service";
+        const f = import("@/utils");
+    "#, // Input codes,
+        r#"
+        import a from "../utils.mjs";
+        import b from "lodash-es";
+        import c from "lodash/fp";
+        import d from "../Button.js";
+        import e from "@scope/pkg";
+        export * from "../helper.service.mjs";
+        const f = import("../utils.mjs");
+    "# // Output codes after transformed with plugin
+    );
+
+    // 补全后缀 (产物后缀) 后, 后续 extensions pass 不再替换: Button.tsx 固定输出 .js
+    test_inline!(
+        Default::default(),
+        |_| {
+            let config = Config {
+                extensions: HashMap::from([
+                    (".js".to_string(), ".mjs".to_string()),
+                    (".mjs".to_string(), ".mjs".to_string()),
+                    (".cjs".to_string(), ".mjs".to_string()),
+                ]),
+                current_dir: Some(format!("{}/tests/fixtures/resolve", env!("CARGO_MANIFEST_DIR"))),
+                resolve_extensions: [".ts", ".tsx", ".mts", ".js"].map(String::from).to_vec(),
+                ..Default::default()
+            };
+            let resolved = ResolvedSpecifiers::default();
+            (
+                resolve_extensions(config.clone(), resolved.clone()),
+                transform_with_resolved(Config { resolve_extensions: vec![], ..config }, resolved),
+            )
+        },
+        fn_resolve_then_extensions,
+        r#"
+        import a from "./utils";
+        import b from "./Button";
+        import c from "./components";
+        import d from "./a.js";
+    "#, // Input codes,
+        r#"
+        import a from "./utils.mjs";
+        import b from "./Button.js";
+        import c from "./components/index.js";
+        import d from "./a.mjs";
+    "# // Output codes after transformed with plugin
+    );
+
+    // 只处理 alias, 保留原后缀
+    test_inline!(
+        Default::default(),
+        |_| {
+            let fixtures = format!("{}/tests/fixtures/resolve", env!("CARGO_MANIFEST_DIR"));
+            alias(Config {
+                extensions: HashMap::from([(".js".to_string(), ".mjs".to_string())]),
+                current_dir: Some(format!("{fixtures}/components")),
+                resolve_extensions: [".ts", ".tsx", ".mts", ".js"].map(String::from).to_vec(),
+                alias: vec![AliasRule {
+                    find: "@".to_string(),
+                    replacements: vec![format!("{fixtures}/not-found"), fixtures.clone()],
+                }],
+                ..Default::default()
+            })
+        },
+        fn_alias_only,
+        r#"
+        import a from "@/utils";
+        import b from "./index.js";
+        import c from "../utils";
+    "#, // Input codes,
+        r#"
+        import a from "../utils";
+        import b from "./index.js";
+        import c from "../utils";
+    "# // Output codes after transformed with plugin
+    );
+
+    #[test]
+    fn test_to_relative_specifier() {
+        assert_eq!(
+            to_relative_specifier(Path::new("/demo/src/utils"), Path::new("/demo/src/components")),
+            Some("../utils".to_string())
+        );
+        assert_eq!(
+            to_relative_specifier(Path::new("/demo/src/utils/index"), Path::new("/demo/src")),
+            Some("./utils/index".to_string())
+        );
+    }
+
+    #[test]
+    fn test_alias_validate() {
+        let rule = |find: &str| AliasRule { find: find.to_string(), replacements: vec![] };
+
+        assert!(rule("@").validate().is_ok());
+        assert!(rule("/^@(\\w+)/").validate().is_ok());
+        assert!(rule("/^@(\\w+/").validate().is_err());
+    }
 }
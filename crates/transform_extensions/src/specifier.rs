@@ -0,0 +1,70 @@
+//! 导入路径所在的位置, 供重写 / 检查 specifier 的 passes 共用
+//!
+//! - `import('./foo')`, `require('./foo')`
+//! - `require.resolve('./foo')`, `import.meta.resolve('./foo')`
+//! - `new URL('./foo', import.meta.url)`
+
+use swc_core::common::collections::AHashSet;
+use swc_core::ecma::ast::{
+    Callee, Expr, Id, Ident, MemberExpr, MemberProp, MetaPropKind, NewExpr, Program,
+};
+use swc_core::ecma::utils::collect_decls;
+
+/// Bindings declared in a program, used to tell the CommonJS `require` from a local one
+///
+/// 依赖 `resolver` 计算的 SyntaxContext; 未经 resolver 时按名称判断 (同名声明即视为局部)
+#[derive(Debug, Default)]
+pub struct Bindings(AHashSet<Id>);
+
+impl Bindings {
+    pub fn new(program: &Program) -> Self {
+        Self(collect_decls(program))
+    }
+
+    /// CommonJS `require` (未在程序内声明)
+    pub fn is_require(&self, i: &Ident) -> bool {
+        i.sym.as_ref() == "require" && !self.0.contains(&i.to_id())
+    }
+
+    /// `import('./foo')`, `require('./foo')`, `require.resolve('./foo')`,
+    /// `import.meta.resolve('./foo')`
+    pub fn is_specifier_call(&self, callee: &Callee) -> bool {
+        let expr = match callee {
+            Callee::Import(_) => return true,
+            Callee::Expr(expr) => &**expr,
+            _ => return false,
+        };
+
+        match expr {
+            Expr::Ident(i) => self.is_require(i),
+            Expr::Member(MemberExpr { obj, prop: MemberProp::Ident(prop), .. }) => {
+                prop.sym.as_ref() == "resolve"
+                    && match &**obj {
+                        Expr::Ident(i) => self.is_require(i),
+                        Expr::MetaProp(meta) => meta.kind == MetaPropKind::ImportMeta,
+                        _ => false,
+                    }
+            }
+            _ => false,
+        }
+    }
+}
+
+/// `import.meta.url`
+pub fn is_import_meta_url(expr: &Expr) -> bool {
+    match expr {
+        Expr::Member(MemberExpr { obj, prop: MemberProp::Ident(prop), .. }) => {
+            prop.sym.as_ref() == "url"
+                && matches!(&**obj, Expr::MetaProp(meta) if meta.kind == MetaPropKind::ImportMeta)
+        }
+        _ => false,
+    }
+}
+
+/// `new URL('./foo', import.meta.url)`
+pub fn is_url_with_import_meta(n: &NewExpr) -> bool {
+    matches!(&*n.callee, Expr::Ident(i) if i.sym.as_ref() == "URL")
+        && n.args
+            .as_deref()
+            .is_some_and(|args| args.len() == 2 && is_import_meta_url(&args[1].expr))
+}
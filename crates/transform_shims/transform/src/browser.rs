@@ -0,0 +1,148 @@
+use swc_core::common::{DUMMY_SP, SyntaxContext};
+use swc_core::ecma::ast::{
+    Decl, Expr, Ident, IdentName, ImportDecl, ImportDefaultSpecifier, ImportSpecifier,
+    KeyValueProp, ModuleDecl, ModuleItem, Pat, Prop, PropName, Stmt, VarDecl, VarDeclKind,
+    VarDeclarator,
+};
+use swc_core::ecma::utils::{ExprFactory, prepend_stmts, private_ident, quote_str};
+use swc_core::ecma::visit::{VisitMut, VisitMutWith, noop_visit_mut_type};
+
+use crate::{Config, ShimsUsage, Target, create_import_decl};
+
+/// 浏览器 targets
+/// - `global` -> `globalThis`
+/// - `process` / `Buffer` -> 仅在引用了它们的文件中注入 polyfill import
+pub(crate) fn transform(
+    items: &mut Vec<ModuleItem>,
+    config: &Config,
+    unresolved_ctxt: SyntaxContext,
+    usage: &mut ShimsUsage,
+) {
+    let mut shims =
+        BrowserShims { config, unresolved_ctxt, usage, process_ident: None, buffer_ident: None };
+    items.visit_mut_with(&mut shims);
+
+    let mut polyfills = vec![];
+
+    // import process from "process/browser"
+    if let (Some(local), Some(src)) = (shims.process_ident, &config.polyfill.process) {
+        polyfills.push(match config.target {
+            Target::CJS => require_var_decl(local, src, None),
+            _ => ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+                span: DUMMY_SP,
+                specifiers: vec![ImportSpecifier::Default(ImportDefaultSpecifier {
+                    span: DUMMY_SP,
+                    local,
+                })],
+                src: Box::new(quote_str!(src.as_str())),
+                type_only: false,
+                with: None,
+                phase: Default::default(),
+            })),
+        });
+    }
+
+    // import { Buffer } from "buffer"
+    if let (Some(local), Some(src)) = (shims.buffer_ident, &config.polyfill.buffer) {
+        polyfills.push(match config.target {
+            Target::CJS => require_var_decl(local, src, Some("Buffer")),
+            _ => ModuleItem::ModuleDecl(ModuleDecl::Import(create_import_decl(
+                local,
+                Some(private_ident!("Buffer")),
+                src,
+            ))),
+        });
+    }
+
+    // 保持 "use client" 等指令在最前
+    prepend_stmts(items, polyfills.into_iter());
+}
+
+/// const local = require(src), const local = require(src).prop
+fn require_var_decl(local: Ident, src: &str, prop: Option<&str>) -> ModuleItem {
+    let require = Ident::new_no_ctxt("require".into(), DUMMY_SP)
+        .as_call(DUMMY_SP, vec![quote_str!(src).as_arg()]);
+
+    let init = match prop {
+        Some(prop) => require.make_member(IdentName::new(prop.into(), DUMMY_SP)).into(),
+        None => require,
+    };
+
+    ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+        span: DUMMY_SP,
+        kind: VarDeclKind::Const,
+        declare: false,
+        decls: vec![VarDeclarator {
+            span: DUMMY_SP,
+            name: Pat::Ident(local.into()),
+            init: Some(Box::new(init)),
+            definite: false,
+        }],
+        ctxt: Default::default(),
+    }))))
+}
+
+struct BrowserShims<'a> {
+    config: &'a Config,
+    unresolved_ctxt: SyntaxContext,
+    usage: &'a mut ShimsUsage,
+    process_ident: Option<Ident>,
+    buffer_ident: Option<Ident>,
+}
+
+impl BrowserShims<'_> {
+    fn is_shimmed(&self, i: &Ident) -> bool {
+        if i.ctxt != self.unresolved_ctxt {
+            return false;
+        }
+
+        match i.sym.as_ref() {
+            "global" => true,
+            "process" => self.config.polyfill.process.is_some(),
+            "Buffer" => self.config.polyfill.buffer.is_some(),
+            _ => false,
+        }
+    }
+}
+
+impl VisitMut for BrowserShims<'_> {
+    noop_visit_mut_type!();
+
+    fn visit_mut_ident(&mut self, i: &mut Ident) {
+        // require 在浏览器中不可用, 仅记录
+        if i.ctxt == self.unresolved_ctxt && i.sym.as_ref() == "require" {
+            self.usage.record("require");
+        }
+
+        if !self.is_shimmed(i) {
+            return;
+        }
+
+        self.usage.record(&i.sym);
+
+        match i.sym.as_ref() {
+            "global" => i.sym = "globalThis".into(),
+            "process" => {
+                *i = self.process_ident.get_or_insert_with(|| private_ident!("process")).clone()
+            }
+            "Buffer" => {
+                *i = self.buffer_ident.get_or_insert_with(|| private_ident!("Buffer")).clone()
+            }
+            _ => {}
+        }
+    }
+
+    // { global } -> { global: globalThis }
+    fn visit_mut_prop(&mut self, p: &mut Prop) {
+        if let Prop::Shorthand(i) = p {
+            if self.is_shimmed(i) {
+                *p = Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(i.clone().into()),
+                    value: Box::new(Expr::Ident(i.clone())),
+                });
+            }
+        }
+
+        p.visit_mut_children_with(self);
+    }
+}
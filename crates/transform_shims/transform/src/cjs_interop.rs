@@ -0,0 +1,190 @@
+use swc_core::common::{DUMMY_SP, Span};
+use swc_core::ecma::ast::{
+    AssignExpr, AssignOp, AssignTarget, BinaryOp, Decl, DefaultDecl, ExportSpecifier, Expr,
+    ExprStmt, Ident, IdentName, Lit, MemberExpr, MemberProp, ModuleDecl, ModuleExportName,
+    ModuleItem, SimpleAssignTarget, Stmt,
+};
+use swc_core::ecma::utils::{ExprFactory, find_pat_ids};
+
+use crate::{ShimsUsage, warn};
+
+// 函数 / 类上的只读属性, 无法通过 Object.assign 覆盖
+const FUNCTION_PROPS: [&str; 5] = ["name", "length", "prototype", "caller", "arguments"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DefaultKind {
+    /// function, arrow function
+    Function,
+    Class,
+    /// string, number, boolean, null, undefined, ...
+    Primitive,
+    Unknown,
+}
+
+#[derive(Default)]
+struct Exports {
+    default: Option<(DefaultKind, Span)>,
+    /// 具名导出 (不含类型导出)
+    names: Vec<(String, Span)>,
+    has_export_all: bool,
+}
+
+/// CJS: `export default` -> `module.exports = default`
+///
+/// - 仅有默认导出: `module.exports = exports.default;`
+/// - 同时有具名导出: `module.exports = Object.assign(exports.default, exports);`
+///
+/// 需在 cjs 模块转换前执行 (依赖 esm 导出声明), 默认导出为原始值或具名导出与函数只读属性冲突时跳过并警告
+pub(crate) fn transform(items: &mut Vec<ModuleItem>, usage: &mut ShimsUsage) {
+    let exports = collect_exports(items);
+
+    let Some((kind, default_span)) = exports.default else {
+        return;
+    };
+
+    let has_named = !exports.names.is_empty() || exports.has_export_all;
+
+    if has_named && kind == DefaultKind::Primitive {
+        let msg = "cjsInterop: the default export is a primitive value, named exports can not be \
+                   attached to `module.exports`";
+        warn(default_span, msg);
+        usage.warnings.push(msg.to_string());
+        return;
+    }
+
+    if matches!(kind, DefaultKind::Function | DefaultKind::Class) {
+        if let Some((name, span)) =
+            exports.names.iter().find(|(name, _)| FUNCTION_PROPS.contains(&name.as_str()))
+        {
+            let msg = format!(
+                "cjsInterop: named export `{name}` conflicts with the read-only `{name}` property \
+                 of the default export"
+            );
+            warn(*span, &msg);
+            usage.warnings.push(msg);
+            return;
+        }
+    }
+
+    let exports_ident = || Ident::new_no_ctxt("exports".into(), DUMMY_SP);
+    let default = exports_ident().make_member(IdentName::new("default".into(), DUMMY_SP));
+
+    // Object.assign(exports.default, exports)
+    let value = if has_named {
+        Ident::new_no_ctxt("Object".into(), DUMMY_SP)
+            .make_member(IdentName::new("assign".into(), DUMMY_SP))
+            .as_call(DUMMY_SP, vec![default.as_arg(), exports_ident().as_arg()])
+    } else {
+        default.into()
+    };
+
+    // module.exports = ...
+    let module_exports = MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::new(Ident::new_no_ctxt("module".into(), DUMMY_SP).into()),
+        prop: MemberProp::Ident(IdentName::new("exports".into(), DUMMY_SP)),
+    };
+
+    items.push(ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(Expr::Assign(AssignExpr {
+            span: DUMMY_SP,
+            op: AssignOp::Assign,
+            left: AssignTarget::Simple(SimpleAssignTarget::Member(module_exports)),
+            right: Box::new(value),
+        })),
+    })));
+
+    usage.record("module.exports");
+}
+
+fn collect_exports(items: &[ModuleItem]) -> Exports {
+    let mut exports = Exports::default();
+
+    for item in items {
+        let ModuleItem::ModuleDecl(decl) = item else {
+            continue;
+        };
+
+        match decl {
+            ModuleDecl::ExportDefaultDecl(n) => {
+                let kind = match &n.decl {
+                    DefaultDecl::Fn(_) => DefaultKind::Function,
+                    DefaultDecl::Class(_) => DefaultKind::Class,
+                    DefaultDecl::TsInterfaceDecl(_) => continue,
+                };
+                exports.default = Some((kind, n.span));
+            }
+            ModuleDecl::ExportDefaultExpr(n) => {
+                exports.default = Some((default_kind(&n.expr), n.span));
+            }
+            ModuleDecl::ExportDecl(n) => match &n.decl {
+                Decl::Var(var) if !var.declare => {
+                    let ids: Vec<Ident> = find_pat_ids(&var.decls);
+                    exports.names.extend(ids.into_iter().map(|id| (id.sym.to_string(), n.span)));
+                }
+                Decl::Fn(f) if !f.declare => exports.names.push((f.ident.sym.to_string(), n.span)),
+                Decl::Class(c) if !c.declare => {
+                    exports.names.push((c.ident.sym.to_string(), n.span))
+                }
+                Decl::TsEnum(e) if !e.declare => exports.names.push((e.id.sym.to_string(), n.span)),
+                _ => {}
+            },
+            ModuleDecl::ExportNamed(n) if !n.type_only => {
+                for specifier in &n.specifiers {
+                    let name = match specifier {
+                        ExportSpecifier::Named(s) if !s.is_type_only => {
+                            export_name(s.exported.as_ref().unwrap_or(&s.orig))
+                        }
+                        ExportSpecifier::Namespace(s) => export_name(&s.name),
+                        ExportSpecifier::Default(s) => s.exported.sym.to_string(),
+                        _ => continue,
+                    };
+
+                    if name == "default" {
+                        exports.default = Some((DefaultKind::Unknown, n.span));
+                    } else {
+                        exports.names.push((name, n.span));
+                    }
+                }
+            }
+            ModuleDecl::ExportAll(n) if !n.type_only => exports.has_export_all = true,
+            _ => {}
+        }
+    }
+
+    exports
+}
+
+fn export_name(name: &ModuleExportName) -> String {
+    match name {
+        ModuleExportName::Ident(i) => i.sym.to_string(),
+        ModuleExportName::Str(s) => s.value.to_atom_lossy().to_string(),
+    }
+}
+
+fn default_kind(expr: &Expr) -> DefaultKind {
+    match expr {
+        Expr::Paren(e) => default_kind(&e.expr),
+        Expr::TsAs(e) => default_kind(&e.expr),
+        Expr::TsSatisfies(e) => default_kind(&e.expr),
+        Expr::TsConstAssertion(e) => default_kind(&e.expr),
+        Expr::TsNonNull(e) => default_kind(&e.expr),
+        Expr::Fn(_) | Expr::Arrow(_) => DefaultKind::Function,
+        Expr::Class(_) => DefaultKind::Class,
+        Expr::Lit(Lit::Regex(_)) | Expr::Lit(Lit::JSXText(_)) => DefaultKind::Unknown,
+        Expr::Lit(_) | Expr::Tpl(_) => DefaultKind::Primitive,
+        Expr::Ident(i) if i.sym.as_ref() == "undefined" => DefaultKind::Primitive,
+        Expr::Unary(_) => DefaultKind::Primitive,
+        // a || b, a ?? b 的结果可能是对象
+        Expr::Bin(e)
+            if !matches!(
+                e.op,
+                BinaryOp::LogicalOr | BinaryOp::LogicalAnd | BinaryOp::NullishCoalescing
+            ) =>
+        {
+            DefaultKind::Primitive
+        }
+        _ => DefaultKind::Unknown,
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use swc_core::common::util::take::Take;
+use swc_core::ecma::ast::{
+    CallExpr, Callee, Decl, Expr, Id, Ident, KeyValuePatProp, KeyValueProp, MemberProp,
+    MetaPropExpr, MetaPropKind, ModuleItem, ObjectPat, ObjectPatProp, Pat, Prop, PropName, Stmt,
+    VarDeclarator,
+};
+use swc_core::ecma::utils::private_ident;
+use swc_core::ecma::visit::{VisitMut, VisitMutWith, noop_visit_mut_type};
+
+use crate::{
+    CJS_DIRNAME, CJS_FILENAME, CJS_RESOLVE, CJS_URL, ShimsUsage, cjs_resolve, is_import_meta_prop,
+};
+
+/// CJS: import.meta -> CommonJS 变量
+/// - `import.meta.dirname`, `import.meta.filename`, `import.meta.url`
+/// - `import.meta.resolve(specifier)`
+/// - `const { dirname, filename } = import.meta`
+///
+/// 需在 cjs 模块转换前执行, swc 的 commonjs 转换会改写 import.meta
+pub(crate) fn transform(items: &mut Vec<ModuleItem>, usage: &mut ShimsUsage) {
+    items.visit_mut_with(&mut ImportMetaShims { usage, id_map: HashMap::new() });
+}
+
+struct ImportMetaShims<'a> {
+    usage: &'a mut ShimsUsage,
+    /// 从 import.meta 解构的变量 -> 属性名
+    id_map: HashMap<Id, String>,
+}
+
+impl VisitMut for ImportMetaShims<'_> {
+    noop_visit_mut_type!();
+
+    // Transform destructured variables from import.meta
+    // e.g., const { dirname, filename } = import.meta
+    fn visit_mut_ident(&mut self, i: &mut Ident) {
+        if let Some(value) = self.id_map.get(&i.to_id()) {
+            let sym = match value.as_str() {
+                "dirname" => CJS_DIRNAME,
+                "filename" => CJS_FILENAME,
+                "url" => CJS_URL,
+                "resolve" => CJS_RESOLVE,
+                _ => return,
+            };
+            self.usage.record(&format!("import.meta.{value}"));
+            i.sym = sym.into();
+        }
+    }
+
+    // { dirname } -> { dirname: __dirname }
+    fn visit_mut_prop(&mut self, p: &mut Prop) {
+        if let Prop::Shorthand(i) = p {
+            if self.id_map.contains_key(&i.to_id()) {
+                *p = Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(i.clone().into()),
+                    value: Box::new(Expr::Ident(i.clone())),
+                });
+            }
+        }
+
+        p.visit_mut_children_with(self);
+    }
+
+    // import.meta.dirname, import.meta.filename, import.meta.url
+    fn visit_mut_expr(&mut self, e: &mut Expr) {
+        e.visit_mut_children_with(self);
+
+        if let Expr::Member(n) = e {
+            if let (Expr::MetaProp(MetaPropExpr { kind, .. }), MemberProp::Ident(prop)) =
+                (&*n.obj, &n.prop)
+            {
+                if *kind == MetaPropKind::ImportMeta {
+                    let sym = match prop.sym.as_ref() {
+                        "dirname" => CJS_DIRNAME,
+                        "filename" => CJS_FILENAME,
+                        "url" => CJS_URL,
+                        _ => return,
+                    };
+                    self.usage.record(&format!("import.meta.{}", prop.sym));
+                    *e = private_ident!(sym).into();
+                }
+            }
+        }
+
+        // import.meta.resolve(specifier)
+        // -> require("url").pathToFileURL(require.resolve(specifier)).href
+        if let Expr::Call(CallExpr { span, callee: Callee::Expr(callee), args, .. }) = e {
+            if is_import_meta_prop(callee, "resolve") {
+                self.usage.record("import.meta.resolve");
+                *e = cjs_resolve(*span, args.take());
+            }
+        }
+    }
+
+    // Maps the destructured identifiers to their import.meta property names
+    fn visit_mut_var_declarator(&mut self, v: &mut VarDeclarator) {
+        v.visit_mut_children_with(self);
+
+        // Check if this is destructuring from import.meta
+        if let Some(Expr::MetaProp(MetaPropExpr { kind: MetaPropKind::ImportMeta, .. })) =
+            v.init.as_deref()
+        {
+            // Extract the destructured property names and map them
+            if let Pat::Object(ObjectPat { props, .. }) = &v.name {
+                props.iter().for_each(|prop| match prop {
+                    ObjectPatProp::KeyValue(KeyValuePatProp { key, value, .. }) => {
+                        if let (Pat::Ident(ident), PropName::Ident(name)) = (&**value, &key) {
+                            self.id_map.insert(ident.to_id(), name.sym.to_string());
+                        }
+                    }
+                    ObjectPatProp::Assign(assign) => {
+                        self.id_map.insert(assign.key.to_id(), assign.key.sym.to_string());
+                    }
+                    _ => (),
+                });
+            }
+
+            // Mark the declarator for removal
+            v.name.take();
+        }
+    }
+
+    // remove var decl ↓↓↓
+    // const { dirname, filename } = import.meta; -> const ;
+    fn visit_mut_var_declarators(&mut self, vars: &mut Vec<VarDeclarator>) {
+        vars.visit_mut_children_with(self);
+
+        // Remove declarators with invalid names (those marked for removal)
+        vars.retain(|node| !node.name.is_invalid());
+    }
+
+    // const ; -> ;
+    fn visit_mut_stmt(&mut self, stmt: &mut Stmt) {
+        stmt.visit_mut_children_with(self);
+
+        if let Stmt::Decl(Decl::Var(var)) = stmt {
+            if var.decls.is_empty() {
+                stmt.take();
+            }
+        }
+    }
+
+    // remove ;
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        stmts.visit_mut_children_with(self);
+
+        stmts.retain(|s| !matches!(s, Stmt::Empty(..)));
+    }
+}
@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+
+use swc_core::common::util::take::Take;
+use swc_core::common::{DUMMY_SP, Span, SyntaxContext};
+use swc_core::ecma::ast::{
+    AssignExpr, AssignOp, AssignTarget, BindingIdent, CallExpr, Callee, Decl, EmptyStmt,
+    ExportDefaultExpr, ExportNamedSpecifier, ExportSpecifier, Expr, ExprStmt, Ident, Lit,
+    MemberExpr, MemberProp, ModuleDecl, ModuleExportName, ModuleItem, NamedExport, Pat,
+    SimpleAssignTarget, Stmt, VarDecl, VarDeclKind, VarDeclarator,
+};
+use swc_core::ecma::utils::private_ident;
+use swc_core::ecma::visit::{
+    Visit, VisitMut, VisitMutWith, VisitWith, noop_visit_mut_type, noop_visit_type,
+};
+
+use crate::error;
+
+/// `module.exports` / `exports.xxx` / `module.exports.xxx`
+enum CjsExport {
+    /// module.exports
+    Default,
+    /// exports.xxx, module.exports.xxx
+    Named(String),
+    /// exports
+    Object,
+}
+
+/// CommonJS -> ESM
+/// - `module.exports = value` -> `export default value`
+/// - `exports.foo = value` -> `var foo = value; export { foo }`
+///
+/// 无法静态分析的用法 (嵌套作用域中给 module.exports 赋值, exports 作为值传递等) 报错
+///
+/// 相对路径的 `require('./x')` 在 esm 产物中指向转换后的 esm 文件, 无法 require, 同样报错
+pub(crate) fn transform(items: &mut Vec<ModuleItem>, unresolved_ctxt: SyntaxContext) {
+    items.visit_with(&mut RelativeRequires { unresolved_ctxt });
+
+    let mut interop =
+        CjsInterop { unresolved_ctxt, locals: HashMap::new(), names: vec![], default_span: None };
+
+    let mut declared = vec![];
+
+    for item in items.iter_mut() {
+        let ModuleItem::Stmt(Stmt::Expr(ExprStmt { span, expr })) = item else {
+            continue;
+        };
+        let span = *span;
+
+        // exports.__esModule = true, Object.defineProperty(exports, "__esModule", ...)
+        if interop.is_es_module_flag(expr) {
+            *item = ModuleItem::Stmt(Stmt::Empty(EmptyStmt { span }));
+            continue;
+        }
+
+        let Expr::Assign(AssignExpr {
+            op: AssignOp::Assign,
+            left: AssignTarget::Simple(SimpleAssignTarget::Member(member)),
+            right,
+            ..
+        }) = &mut **expr
+        else {
+            continue;
+        };
+
+        match interop.classify(member) {
+            // module.exports = value -> export default value
+            Some(CjsExport::Default) => {
+                if let Some(default_span) = interop.default_span {
+                    error(span, "`module.exports` is assigned more than once");
+                    error(default_span, "first assigned here");
+                    *item = ModuleItem::Stmt(Stmt::Empty(EmptyStmt { span }));
+                    continue;
+                }
+                interop.default_span = Some(span);
+
+                let mut right = right.take();
+                right.visit_mut_with(&mut interop);
+
+                *item = ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(ExportDefaultExpr {
+                    span,
+                    expr: right,
+                }));
+            }
+            // exports.foo = value -> var foo = value
+            Some(CjsExport::Named(name)) if !declared.contains(&name) => {
+                let local = interop.local(&name);
+                declared.push(name);
+
+                let mut right = right.take();
+                right.visit_mut_with(&mut interop);
+
+                *item = ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(var_decl(
+                    span,
+                    local,
+                    Some(right),
+                )))));
+            }
+            _ => {}
+        }
+    }
+
+    // 其余的 exports.foo (读取, 重复赋值, 嵌套作用域) -> foo
+    items.visit_mut_with(&mut interop);
+    items.retain(|item| !matches!(item, ModuleItem::Stmt(Stmt::Empty(..))));
+
+    if interop.names.is_empty() {
+        return;
+    }
+
+    if let Some(default_span) = interop.default_span {
+        error(default_span, "`module.exports = ...` can not be mixed with `exports.xxx = ...`");
+        return;
+    }
+
+    // 仅在嵌套作用域中赋值的导出: var foo;
+    for name in interop.names.iter().filter(|name| !declared.contains(name)) {
+        let local = interop.locals[name].clone();
+        items.insert(
+            0,
+            ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(var_decl(DUMMY_SP, local, None))))),
+        );
+    }
+
+    // export { foo, _default as default }
+    let specifiers = interop
+        .names
+        .iter()
+        .map(|name| {
+            ExportSpecifier::Named(ExportNamedSpecifier {
+                span: DUMMY_SP,
+                orig: ModuleExportName::Ident(interop.locals[name].clone()),
+                exported: Some(ModuleExportName::Ident(Ident::new_no_ctxt(
+                    name.as_str().into(),
+                    DUMMY_SP,
+                ))),
+                is_type_only: false,
+            })
+        })
+        .collect();
+
+    items.push(ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+        span: DUMMY_SP,
+        specifiers,
+        src: None,
+        type_only: false,
+        with: None,
+    })));
+}
+
+fn var_decl(span: Span, local: Ident, init: Option<Box<Expr>>) -> VarDecl {
+    VarDecl {
+        span,
+        kind: VarDeclKind::Var,
+        declare: false,
+        decls: vec![VarDeclarator {
+            span: DUMMY_SP,
+            name: Pat::Ident(local.into()),
+            init,
+            definite: false,
+        }],
+        ctxt: Default::default(),
+    }
+}
+
+/// 相对路径且非 .json / .node / .cjs 的 require
+fn is_relative_module(src: &str) -> bool {
+    let relative = src == "." || src == ".." || src.starts_with("./") || src.starts_with("../");
+    relative && ![".json", ".node", ".cjs"].iter().any(|ext| src.ends_with(ext))
+}
+
+struct RelativeRequires {
+    unresolved_ctxt: SyntaxContext,
+}
+
+impl Visit for RelativeRequires {
+    noop_visit_type!();
+
+    // require('./utils')
+    fn visit_call_expr(&mut self, n: &CallExpr) {
+        n.visit_children_with(self);
+
+        let Callee::Expr(callee) = &n.callee else {
+            return;
+        };
+        let Expr::Ident(i) = &**callee else {
+            return;
+        };
+        if i.ctxt != self.unresolved_ctxt || i.sym.as_ref() != "require" {
+            return;
+        }
+
+        if let Some(Expr::Lit(Lit::Str(src))) = n.args.first().map(|arg| &*arg.expr) {
+            if is_relative_module(&src.value.to_atom_lossy()) {
+                error(
+                    n.span,
+                    "`require` of a relative module can not be converted to ESM, use `import` instead",
+                );
+            }
+        }
+    }
+}
+
+struct CjsInterop {
+    unresolved_ctxt: SyntaxContext,
+    /// export name -> local ident
+    locals: HashMap<String, Ident>,
+    /// export names, in order of appearance
+    names: Vec<String>,
+    default_span: Option<Span>,
+}
+
+impl CjsInterop {
+    fn is_unresolved(&self, expr: &Expr, sym: &str) -> bool {
+        matches!(expr, Expr::Ident(i) if i.ctxt == self.unresolved_ctxt && i.sym.as_ref() == sym)
+    }
+
+    fn classify(&self, member: &MemberExpr) -> Option<CjsExport> {
+        let name = match &member.prop {
+            MemberProp::Ident(prop) => Some(prop.sym.to_string()),
+            _ => None,
+        };
+
+        // module.exports
+        if self.is_unresolved(&member.obj, "module") {
+            return (name.as_deref() == Some("exports")).then_some(CjsExport::Default);
+        }
+
+        // exports.xxx, module.exports.xxx
+        let is_exports = self.is_unresolved(&member.obj, "exports")
+            || matches!(&*member.obj, Expr::Member(obj) if matches!(self.classify(obj), Some(CjsExport::Default)));
+
+        match (is_exports, name) {
+            (false, _) => None,
+            (true, Some(name)) => Some(CjsExport::Named(name)),
+            // exports[key]
+            (true, None) => Some(CjsExport::Object),
+        }
+    }
+
+    fn is_es_module_flag(&self, expr: &Expr) -> bool {
+        match expr {
+            // exports.__esModule = true
+            Expr::Assign(AssignExpr {
+                left: AssignTarget::Simple(SimpleAssignTarget::Member(member)),
+                ..
+            }) => {
+                matches!(self.classify(member), Some(CjsExport::Named(name)) if name == "__esModule")
+            }
+            // Object.defineProperty(exports, "__esModule", { value: true })
+            Expr::Call(call) => {
+                let Callee::Expr(callee) = &call.callee else {
+                    return false;
+                };
+                let is_define_property = matches!(&**callee, Expr::Member(MemberExpr {
+                    obj,
+                    prop: MemberProp::Ident(prop),
+                    ..
+                }) if self.is_unresolved(obj, "Object") && prop.sym.as_ref() == "defineProperty");
+
+                is_define_property
+                    && call.args.first().is_some_and(|arg| self.is_unresolved(&arg.expr, "exports"))
+                    && matches!(
+                        call.args.get(1).map(|arg| &*arg.expr),
+                        Some(Expr::Lit(Lit::Str(s))) if s.value == "__esModule"
+                    )
+            }
+            _ => false,
+        }
+    }
+
+    fn local(&mut self, name: &str) -> Ident {
+        if let Some(local) = self.locals.get(name) {
+            return local.clone();
+        }
+
+        // exports.default -> _default
+        let local = if Ident::verify_symbol(name).is_ok() {
+            private_ident!(name)
+        } else {
+            private_ident!(format!("_{name}"))
+        };
+
+        self.names.push(name.to_string());
+        self.locals.insert(name.to_string(), local.clone());
+        local
+    }
+}
+
+impl VisitMut for CjsInterop {
+    noop_visit_mut_type!();
+
+    fn visit_mut_expr(&mut self, e: &mut Expr) {
+        if let Expr::Member(member) = e {
+            match self.classify(member) {
+                Some(CjsExport::Named(name)) => {
+                    *e = Expr::Ident(self.local(&name));
+                    return;
+                }
+                Some(CjsExport::Default) => {
+                    error(member.span, "`module.exports` can not be statically converted to ESM");
+                    return;
+                }
+                Some(CjsExport::Object) => {
+                    error(member.span, "`exports[...]` can not be statically converted to ESM");
+                    return;
+                }
+                None => {}
+            }
+        }
+
+        if self.is_unresolved(e, "exports") {
+            if let Expr::Ident(i) = e {
+                error(i.span, "`exports` can not be statically converted to ESM");
+            }
+            return;
+        }
+
+        e.visit_mut_children_with(self);
+    }
+
+    // exports.foo = value (嵌套作用域, 重复赋值) -> foo = value
+    fn visit_mut_simple_assign_target(&mut self, target: &mut SimpleAssignTarget) {
+        if let SimpleAssignTarget::Member(member) = target {
+            match self.classify(member) {
+                Some(CjsExport::Named(name)) => {
+                    *target = SimpleAssignTarget::Ident(BindingIdent::from(self.local(&name)));
+                    return;
+                }
+                Some(CjsExport::Default) => {
+                    error(
+                        member.span,
+                        "`module.exports = ...` is only supported as a top-level statement",
+                    );
+                    return;
+                }
+                Some(CjsExport::Object) => {
+                    error(member.span, "`exports[...]` can not be statically converted to ESM");
+                    return;
+                }
+                None => {}
+            }
+        }
+
+        target.visit_mut_children_with(self);
+    }
+}
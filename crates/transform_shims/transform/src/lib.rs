@@ -1,18 +1,25 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
 
-use serde::Deserialize;
-use swc_core::common::util::take::Take;
-use swc_core::common::{DUMMY_SP, Mark, SyntaxContext};
+use serde::{Deserialize, Serialize};
+use swc_core::common::errors::HANDLER;
+use swc_core::common::{DUMMY_SP, Mark, Span, SyntaxContext};
 use swc_core::ecma::ast::{
-    CallExpr, Callee, Decl, Expr, ExprOrSpread, Id, Ident, ImportDecl, ImportNamedSpecifier,
-    ImportSpecifier, KeyValuePatProp, MemberExpr, MemberProp, MetaPropExpr, MetaPropKind,
-    ModuleDecl, ModuleExportName, ModuleItem, ObjectPat, ObjectPatProp, Pass, Pat, PropName, Stmt,
-    VarDecl, VarDeclKind, VarDeclarator,
+    CallExpr, Callee, Decl, Expr, ExprOrSpread, Ident, IdentName, ImportDecl, ImportNamedSpecifier,
+    ImportSpecifier, KeyValueProp, MemberExpr, MemberProp, MetaPropExpr, MetaPropKind, ModuleDecl,
+    ModuleExportName, ModuleItem, Pass, Pat, Program, Prop, PropName, Stmt, VarDecl, VarDeclKind,
+    VarDeclarator,
 };
 use swc_core::ecma::transforms::base::resolver;
-use swc_core::ecma::utils::{private_ident, quote_str};
+use swc_core::ecma::utils::{ExprFactory, prepend_stmts, private_ident, quote_str};
 use swc_core::ecma::visit::{VisitMut, VisitMutWith, noop_visit_mut_type, visit_mut_pass};
 
+mod browser;
+mod cjs_interop;
+mod import_meta;
+mod interop;
+mod top_level_await;
+
 #[derive(Clone, Debug, Deserialize, Default, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum Target {
@@ -23,6 +30,24 @@ pub enum Target {
     CJS,
 }
 
+#[derive(Clone, Debug, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    #[default]
+    Node,
+    Browser,
+}
+
+/// 浏览器 targets 下 Node 全局变量的 polyfill 包, 仅在引用了它们的文件中注入
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Polyfill {
+    /// import process from "process/browser"
+    pub process: Option<String>,
+    /// import { Buffer } from "buffer"
+    pub buffer: Option<String>,
+}
+
 #[derive(Clone, Debug, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
@@ -30,21 +55,84 @@ pub struct Config {
     pub legacy: bool,
 
     pub target: Target,
+
+    /// ESM: module.exports / exports.xxx -> export default / export { xxx }
+    #[serde(default)]
+    pub interop: bool,
+
+    /// CJS: export default -> module.exports = default, 需在 cjs 模块转换前执行
+    #[serde(default)]
+    pub cjs_interop: bool,
+
+    /// CJS: top-level await 包裹为 async IIFE, 否则报错
+    #[serde(default)]
+    pub top_level_await: bool,
+
+    /// CJS: import.meta, top-level await 已由 [import_meta], [top_level_await] 在 cjs 模块转换前处理,
+    /// 本 pass 跳过
+    #[serde(default)]
+    pub skip_module_syntax: bool,
+
+    /// browser: 不注入 Node shims, global -> globalThis
+    #[serde(default)]
+    pub platform: Platform,
+
+    #[serde(default)]
+    pub polyfill: Polyfill,
+}
+
+/// Shims applied to a single file: shimmed global / injected helper -> count
+///
+/// - `require`, `__dirname`, `__filename` (esm)
+/// - `import.meta.dirname`, `import.meta.filename`, `import.meta.url`, `import.meta.resolve` (cjs)
+/// - `global`, `process`, `Buffer` (browser), `require` is kept as is in browser mode
+/// - `createRequire`, `fileURLToPath`: injected imports
+/// - `module.exports`: default export assigned to `module.exports` (cjs interop)
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ShimsUsage {
+    pub hits: BTreeMap<String, usize>,
+    /// 跳过的不安全转换
+    pub warnings: Vec<String>,
+}
+
+impl ShimsUsage {
+    pub fn record(&mut self, name: &str) {
+        *self.hits.entry(name.to_string()).or_default() += 1;
+    }
+
+    pub fn merge(&mut self, other: &ShimsUsage) {
+        for (name, count) in &other.hits {
+            *self.hits.entry(name.clone()).or_default() += count;
+        }
+        self.warnings.extend(other.warnings.iter().cloned());
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.hits.contains_key(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hits.is_empty() && self.warnings.is_empty()
+    }
 }
 
 struct TransformShims {
     config: Config,
-    id_map: HashMap<Id, String>,
+    usage: ShimsUsage,
+    stats: Option<Arc<Mutex<ShimsUsage>>>,
     has_legacy_transform: bool,
     has_require_transform: bool,
     require_ident: Option<Ident>,
-    create_require_ident: Option<Ident>,
+    /// `__require` 已由其他 pass 注入 (见 [find_create_require])
+    require_injected: bool,
     unresolved_ctxt: SyntaxContext,
 }
 
 const CJS_DIRNAME: &str = "__dirname";
 const CJS_FILENAME: &str = "__filename";
 const CJS_URL: &str = "require(\"url\").pathToFileURL(__filename).toString()";
+const CJS_RESOLVE: &str =
+    "((specifier)=>require(\"url\").pathToFileURL(require.resolve(specifier)).href)";
 
 const ESM_DIRNAME: &str = "import.meta.dirname";
 const ESM_FILENAME: &str = "import.meta.filename";
@@ -114,133 +202,192 @@ fn create_require_var_decl(require_ident: Ident, create_require_ident: Ident) ->
     }
 }
 
-impl VisitMut for TransformShims {
-    noop_visit_mut_type!();
+/// import { createRequire as _createRequire } from "node:module";
+/// const __require = _createRequire(import.meta.url);
+pub fn create_require(require_ident: Ident) -> [ModuleItem; 2] {
+    let create_require_ident = private_ident!("_createRequire");
 
-    // esm support __dirname, __filename, require
-    fn visit_mut_ident(&mut self, i: &mut Ident) {
-        match self.config.target {
-            Target::ESM => {
-                // ESM: require -> __require (using the shared identifier)
-                if i.sym.as_ref() == "require" {
-                    let require_ident =
-                        self.require_ident.get_or_insert_with(|| private_ident!("__require"));
-                    *i = require_ident.clone();
-                    self.has_require_transform = true;
-                    return;
-                }
+    let require_var_decl = create_require_var_decl(require_ident, create_require_ident.clone());
+    let import_decl = create_import_decl(
+        create_require_ident,
+        Some(private_ident!("createRequire")),
+        "node:module",
+    );
 
-                // Transform __dirname and __filename for ESM
-                // Check if the identifier is in the unresolved scope (not shadowed)
-                if i.ctxt != self.unresolved_ctxt {
-                    return;
-                }
+    [
+        ModuleItem::ModuleDecl(ModuleDecl::Import(import_decl)),
+        ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(require_var_decl)))),
+    ]
+}
 
-                let sym = match (i.sym.as_ref(), self.config.legacy) {
-                    ("__dirname", true) => {
-                        self.has_legacy_transform = true;
-                        ESM_DIRNAME_LEGACY
-                    }
-                    ("__dirname", false) => ESM_DIRNAME,
-                    ("__filename", true) => {
-                        self.has_legacy_transform = true;
-                        ESM_FILENAME_LEGACY
-                    }
-                    ("__filename", false) => ESM_FILENAME,
-                    _ => return,
-                };
-                i.sym = sym.into();
-            }
-            Target::CJS => {
-                // Transform destructured variables from import.meta
-                // e.g., const { dirname, filename } = import.meta
-                if let Some(value) = self.id_map.get(&i.to_id()) {
-                    let sym = match value.as_str() {
-                        "dirname" => CJS_DIRNAME,
-                        "filename" => CJS_FILENAME,
-                        "url" => CJS_URL,
-                        _ => return,
-                    };
-                    i.sym = sym.into();
-                }
-            }
-            Target::UNKNOWN => {}
+/// [create_require] 注入的 `__require`: `const __require = _createRequire(import.meta.url)`,
+/// `_createRequire` 导入自 "node:module"
+pub fn find_create_require(items: &[ModuleItem]) -> Option<Ident> {
+    let create_require = items.iter().find_map(|item| match item {
+        ModuleItem::ModuleDecl(ModuleDecl::Import(decl)) if decl.src.value == "node:module" => {
+            decl.specifiers.iter().find_map(|spec| match spec {
+                ImportSpecifier::Named(ImportNamedSpecifier {
+                    local,
+                    imported: Some(ModuleExportName::Ident(imported)),
+                    ..
+                }) if imported.sym.as_ref() == "createRequire" => Some(local.to_id()),
+                _ => None,
+            })
         }
+        _ => None,
+    })?;
+
+    items.iter().find_map(|item| {
+        let ModuleItem::Stmt(Stmt::Decl(Decl::Var(var))) = item else {
+            return None;
+        };
+        var.decls.iter().find_map(|decl| match (&decl.name, decl.init.as_deref()) {
+            (
+                Pat::Ident(require),
+                Some(Expr::Call(CallExpr { callee: Callee::Expr(callee), args, .. })),
+            ) if matches!(&**callee, Expr::Ident(i) if i.to_id() == create_require)
+                && args.first().is_some_and(|arg| is_import_meta_prop(&arg.expr, "url")) =>
+            {
+                Some(require.id.clone())
+            }
+            _ => None,
+        })
+    })
+}
+
+fn is_import_meta_prop(expr: &Expr, name: &str) -> bool {
+    matches!(expr, Expr::Member(MemberExpr { obj, prop: MemberProp::Ident(prop), .. })
+        if matches!(&**obj, Expr::MetaProp(MetaPropExpr { kind: MetaPropKind::ImportMeta, .. }))
+            && prop.sym.as_ref() == name)
+}
+
+/// require("url").pathToFileURL(require.resolve(...args)).href
+fn cjs_resolve(span: Span, args: Vec<ExprOrSpread>) -> Expr {
+    let require = || Ident::new_no_ctxt("require".into(), DUMMY_SP);
+
+    let resolved =
+        require().make_member(IdentName::new("resolve".into(), DUMMY_SP)).as_call(DUMMY_SP, args);
+
+    require()
+        .as_call(DUMMY_SP, vec![quote_str!("url").as_arg()])
+        .make_member(IdentName::new("pathToFileURL".into(), DUMMY_SP))
+        .as_call(span, vec![resolved.as_arg()])
+        .make_member(IdentName::new("href".into(), DUMMY_SP))
+        .into()
+}
+
+/// Emit a warning through the swc handler (if any)
+fn warn(span: Span, msg: &str) {
+    if HANDLER.is_set() {
+        HANDLER.with(|handler| handler.struct_span_warn(span, msg).emit());
     }
+}
 
-    // cjs support import.meta.dirname, import.meta.filename
-    fn visit_mut_expr(&mut self, e: &mut Expr) {
-        e.visit_mut_children_with(self);
+/// Emit an error through the swc handler (if any)
+pub(crate) fn error(span: Span, msg: &str) {
+    if HANDLER.is_set() {
+        HANDLER.with(|handler| handler.struct_span_err(span, msg).emit());
+    }
+}
 
-        if self.config.target == Target::CJS {
-            if let Expr::Member(n) = e {
-                if let (Expr::MetaProp(MetaPropExpr { kind, .. }), MemberProp::Ident(prop)) =
-                    (&*n.obj, &n.prop)
-                {
-                    if *kind == MetaPropKind::ImportMeta {
-                        let sym = match prop.sym.as_ref() {
-                            "dirname" => CJS_DIRNAME,
-                            "filename" => CJS_FILENAME,
-                            _ => return,
-                        };
-                        *e = private_ident!(sym).into();
-                    }
-                }
-            }
-        }
+impl TransformShims {
+    /// Whether the identifier refers to a global (not shadowed by a local binding)
+    fn is_unresolved(&self, i: &Ident, sym: &str) -> bool {
+        i.ctxt == self.unresolved_ctxt && i.sym.as_ref() == sym
     }
 
-    // CJS support: Transform destructured import.meta variables
-    // e.g., const { dirname, filename } = import.meta
-    // Maps the destructured identifiers to their import.meta property names
-    fn visit_mut_var_declarator(&mut self, v: &mut VarDeclarator) {
-        v.visit_mut_children_with(self);
-
-        // Only process for CJS target
-        if self.config.target == Target::CJS {
-            // Check if this is destructuring from import.meta
-            if let Some(Expr::MetaProp(MetaPropExpr { kind: MetaPropKind::ImportMeta, .. })) =
-                v.init.as_deref()
-            {
-                // Extract the destructured property names and map them
-                if let Pat::Object(ObjectPat { props, .. }) = &v.name {
-                    props.iter().for_each(|prop| match prop {
-                        ObjectPatProp::KeyValue(KeyValuePatProp { key, value, .. }) => {
-                            if let (Pat::Ident(ident), PropName::Ident(name)) = (&**value, &key) {
-                                self.id_map.insert(ident.to_id(), name.sym.to_string());
-                            }
-                        }
-                        ObjectPatProp::Assign(assign) => {
-                            self.id_map.insert(assign.key.to_id(), assign.key.sym.to_string());
-                        }
-                        _ => (),
-                    });
-                }
+    /// Whether `visit_mut_ident` will rewrite the identifier
+    fn is_shimmed(&self, i: &Ident) -> bool {
+        self.config.target == Target::ESM
+            && ["require", "__dirname", "__filename"].iter().any(|sym| self.is_unresolved(i, sym))
+    }
+
+    // ESM: CJS-only globals that can not be shimmed
+    fn check_esm_member(&self, n: &MemberExpr) {
+        let (Expr::Ident(obj), MemberProp::Ident(prop)) = (&*n.obj, &n.prop) else {
+            return;
+        };
 
-                // Mark the declarator for removal
-                v.name.take();
+        let msg = match (obj.sym.as_ref(), prop.sym.as_ref()) {
+            ("module", "exports") if self.is_unresolved(obj, "module") => {
+                "`module.exports` is not available in ESM output, use `export` instead"
             }
-        }
+            ("exports", _) if self.is_unresolved(obj, "exports") => {
+                "`exports` is not available in ESM output, use `export` instead"
+            }
+            ("require", "cache") if self.is_unresolved(obj, "require") => {
+                "`require.cache` only contains CommonJS modules in ESM output, ES modules are \
+                 not cached in it"
+            }
+            _ => return,
+        };
+
+        warn(n.span, msg);
     }
+}
 
-    // remove var decl ↓↓↓
-    // const { dirname, filename } = import.meta; -> const ;
-    fn visit_mut_var_declarators(&mut self, vars: &mut Vec<VarDeclarator>) {
-        vars.visit_mut_children_with(self);
+impl VisitMut for TransformShims {
+    noop_visit_mut_type!();
+
+    // esm support __dirname, __filename, require
+    fn visit_mut_ident(&mut self, i: &mut Ident) {
+        // Check if the identifier is in the unresolved scope (not shadowed)
+        if self.config.target != Target::ESM || i.ctxt != self.unresolved_ctxt {
+            return;
+        }
+
+        // ESM: require -> __require (using the shared identifier)
+        // require.resolve -> __require.resolve
+        if i.sym.as_ref() == "require" {
+            let require_ident =
+                self.require_ident.get_or_insert_with(|| private_ident!("__require"));
+            *i = require_ident.clone();
+            self.has_require_transform = true;
+            self.usage.record("require");
+            return;
+        }
 
-        // Remove declarators with invalid names (those marked for removal)
-        vars.retain(|node| !node.name.is_invalid());
+        // Transform __dirname and __filename for ESM
+        let sym = match (i.sym.as_ref(), self.config.legacy) {
+            ("__dirname", true) => {
+                self.has_legacy_transform = true;
+                ESM_DIRNAME_LEGACY
+            }
+            ("__dirname", false) => ESM_DIRNAME,
+            ("__filename", true) => {
+                self.has_legacy_transform = true;
+                ESM_FILENAME_LEGACY
+            }
+            ("__filename", false) => ESM_FILENAME,
+            _ => return,
+        };
+        self.usage.record(&i.sym);
+        i.sym = sym.into();
     }
 
-    // const ; -> ;
-    fn visit_mut_stmt(&mut self, stmt: &mut Stmt) {
-        stmt.visit_mut_children_with(self);
+    // { require } -> { require: __require }
+    fn visit_mut_prop(&mut self, p: &mut Prop) {
+        if let Prop::Shorthand(i) = p {
+            if self.is_shimmed(i) {
+                *p = Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(i.clone().into()),
+                    value: Box::new(Expr::Ident(i.clone())),
+                });
+            }
+        }
+
+        p.visit_mut_children_with(self);
+    }
 
-        if let Stmt::Decl(Decl::Var(var)) = stmt {
-            if var.decls.is_empty() {
-                stmt.take();
+    fn visit_mut_expr(&mut self, e: &mut Expr) {
+        // 需在 require -> __require 之前检查
+        if self.config.target == Target::ESM {
+            if let Expr::Member(n) = e {
+                self.check_esm_member(n);
             }
         }
+
+        e.visit_mut_children_with(self);
     }
 
     // remove ;
@@ -253,6 +400,38 @@ impl VisitMut for TransformShims {
     }
 
     fn visit_mut_module_items(&mut self, items: &mut Vec<ModuleItem>) {
+        // 需在 require -> __require 之前处理
+        if self.config.target == Target::ESM && self.config.interop {
+            interop::transform(items, self.unresolved_ctxt);
+        }
+
+        let cjs_module_syntax =
+            self.config.target == Target::CJS && !self.config.skip_module_syntax;
+
+        if self.config.target == Target::CJS && self.config.cjs_interop {
+            cjs_interop::transform(items, &mut self.usage);
+        }
+        if cjs_module_syntax {
+            top_level_await::transform(items, self.config.top_level_await);
+        }
+
+        if self.config.platform == Platform::Browser {
+            browser::transform(items, &self.config, self.unresolved_ctxt, &mut self.usage);
+            return;
+        }
+
+        if cjs_module_syntax {
+            import_meta::transform(items, &mut self.usage);
+        }
+
+        // 复用已注入的 __require (如 json 导入), 避免重复声明
+        if self.config.target == Target::ESM {
+            if let Some(require_ident) = find_create_require(items) {
+                self.require_ident = Some(require_ident);
+                self.require_injected = true;
+            }
+        }
+
         items.visit_mut_children_with(self);
 
         // Add import { fileURLToPath } from "node:url" for legacy ESM
@@ -261,20 +440,21 @@ impl VisitMut for TransformShims {
             if !has_import_specifier(items, "node:url", "fileURLToPath") {
                 let import_decl =
                     create_import_decl(private_ident!("fileURLToPath"), None, "node:url");
-                items.insert(0, ModuleItem::ModuleDecl(ModuleDecl::Import(import_decl)));
+                // 插入到 "use client" 等指令之后
+                prepend_stmts(
+                    items,
+                    std::iter::once(ModuleItem::ModuleDecl(ModuleDecl::Import(import_decl))),
+                );
+                self.usage.record("fileURLToPath");
             }
         }
 
         // Add import { createRequire } from "node:module" and const __require = createRequire(import.meta.url)
         if self.config.target == Target::ESM && self.has_require_transform {
             // Only check if import exists when we need to add it
-            if !has_import_specifier(items, "node:module", "createRequire") {
-                // Create or reuse the shared identifier for createRequire
-                let create_require_ident = self
-                    .create_require_ident
-                    .get_or_insert_with(|| private_ident!("_createRequire"))
-                    .clone();
-
+            if !self.require_injected
+                && !has_import_specifier(items, "node:module", "createRequire")
+            {
                 // Use the same __require identifier that was created during visit_mut_ident
                 let require_ident = self
                     .require_ident
@@ -282,26 +462,79 @@ impl VisitMut for TransformShims {
                     .cloned()
                     .unwrap_or_else(|| private_ident!("__require"));
 
-                // const __require = _createRequire(import.meta.url);
-                let require_var_decl =
-                    create_require_var_decl(require_ident, create_require_ident.clone());
+                prepend_stmts(items, create_require(require_ident).into_iter());
+                self.usage.record("createRequire");
+            }
+        }
+    }
 
-                // import { createRequire as _createRequire } from "node:module";
-                let import_decl = create_import_decl(
-                    create_require_ident,
-                    Some(private_ident!("createRequire")),
-                    "node:module",
-                );
+    fn visit_mut_program(&mut self, program: &mut Program) {
+        program.visit_mut_children_with(self);
 
-                items.insert(0, ModuleItem::ModuleDecl(ModuleDecl::Import(import_decl)));
-                items
-                    .insert(1, ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(require_var_decl)))));
-            }
+        if let Some(stats) = &self.stats {
+            stats.lock().unwrap().merge(&self.usage);
         }
     }
 }
 
 pub fn transform(config: Config) -> impl Pass {
+    transform_inner(config, None)
+}
+
+/// Same as [transform], and records the applied shims into `stats`
+pub fn transform_with_stats(config: Config, stats: Arc<Mutex<ShimsUsage>>) -> impl Pass {
+    transform_inner(config, Some(stats))
+}
+
+/// CJS interop only (`module.exports = default`), for pipelines where the shims pass runs after
+/// the commonjs module transform, this must run before it
+pub fn cjs_interop(stats: Option<Arc<Mutex<ShimsUsage>>>) -> impl Pass {
+    module_items_pass(stats, cjs_interop::transform)
+}
+
+/// CJS top-level await only (see [Config::top_level_await]), must run before the commonjs module
+/// transform: ESM exports are checked before they become `exports.x = ...` assignments
+pub fn top_level_await(wrap: bool) -> impl Pass {
+    module_items_pass(None, move |items, _| top_level_await::transform(items, wrap))
+}
+
+/// CJS `import.meta` shims only (`import.meta.url` -> `require("url")...`), must run before the
+/// commonjs module transform, which rewrites `import.meta` itself
+pub fn import_meta(stats: Option<Arc<Mutex<ShimsUsage>>>) -> impl Pass {
+    module_items_pass(stats, import_meta::transform)
+}
+
+/// 仅处理顶层 module items 的 pass, 不运行 resolver (依赖调用方已计算的 SyntaxContext)
+fn module_items_pass<F>(stats: Option<Arc<Mutex<ShimsUsage>>>, transform: F) -> impl Pass
+where
+    F: FnMut(&mut Vec<ModuleItem>, &mut ShimsUsage),
+{
+    struct ModuleItemsPass<F> {
+        transform: F,
+        usage: ShimsUsage,
+        stats: Option<Arc<Mutex<ShimsUsage>>>,
+    }
+
+    impl<F: FnMut(&mut Vec<ModuleItem>, &mut ShimsUsage)> VisitMut for ModuleItemsPass<F> {
+        noop_visit_mut_type!();
+
+        fn visit_mut_module_items(&mut self, items: &mut Vec<ModuleItem>) {
+            (self.transform)(items, &mut self.usage);
+        }
+
+        fn visit_mut_program(&mut self, program: &mut Program) {
+            program.visit_mut_children_with(self);
+
+            if let Some(stats) = &self.stats {
+                stats.lock().unwrap().merge(&self.usage);
+            }
+        }
+    }
+
+    visit_mut_pass(ModuleItemsPass { transform, usage: Default::default(), stats })
+}
+
+fn transform_inner(config: Config, stats: Option<Arc<Mutex<ShimsUsage>>>) -> impl Pass {
     let unresolved_mark = Mark::new();
     let top_level_mark = Mark::new();
 
@@ -309,11 +542,12 @@ pub fn transform(config: Config) -> impl Pass {
         resolver(unresolved_mark, top_level_mark, true),
         visit_mut_pass(TransformShims {
             config,
-            id_map: HashMap::new(),
+            usage: Default::default(),
+            stats,
             has_legacy_transform: false,
             has_require_transform: false,
             require_ident: None,
-            create_require_ident: None,
+            require_injected: false,
             unresolved_ctxt: SyntaxContext::empty().apply_mark(unresolved_mark),
         }),
     )
@@ -321,6 +555,7 @@ pub fn transform(config: Config) -> impl Pass {
 
 #[cfg(test)]
 mod tests {
+    use swc_core::ecma::parser::Syntax;
     use swc_core::ecma::transforms::testing::test_inline;
 
     use super::*;
@@ -377,6 +612,25 @@ mod tests {
         "# // Output codes after transformed with plugin
     );
 
+    test_inline!(
+        Default::default(),
+        |_| transform(serde_json::from_str(r#"{"target":"esm","legacy":true}"#).unwrap()),
+        fn_shims_esm_directives,
+        r#"
+            "use client";
+            console.log(__dirname, require("./a.json"));
+            export {}
+        "#, // Input codes,
+        r#"
+            "use client";
+            import { createRequire as _createRequire } from "node:module";
+            const __require = _createRequire(import.meta.url);
+            import { fileURLToPath } from "node:url";
+            console.log(fileURLToPath(new URL('.', import.meta.url)), __require("./a.json"));
+            export {}
+        "# // Output codes after transformed with plugin
+    );
+
     test_inline!(
         Default::default(),
         |_| transform(serde_json::from_str(r#"{"target":"cjs"}"#).unwrap()),
@@ -388,7 +642,7 @@ mod tests {
 
             console.log(import.meta.dirname);
             console.log(import.meta.filename);
-            // console.log(import.meta.url); // 无需处理, swc 支持转换
+            console.log(import.meta.url);
         "#, // Input codes,
         r#"
             const __dirname1 = "1";
@@ -397,7 +651,7 @@ mod tests {
 
             console.log(__dirname);
             console.log(__filename);
-            // console.log(require("url").pathToFileURL(__filename).toString());
+            console.log(require("url").pathToFileURL(__filename).toString());
         "# // Output codes after transformed with plugin
     );
 
@@ -686,4 +940,383 @@ mod tests {
             export {}
         "# // Output codes after transformed with plugin
     );
+
+    test_inline!(
+        Default::default(),
+        |_| transform(serde_json::from_str(r#"{"target":"esm"}"#).unwrap()),
+        fn_shims_esm_require_shadowing,
+        r#"
+            function load(require) {
+                return require('./a.js');
+            }
+            {
+                const require = (id) => id;
+                console.log(require('./b.js'));
+            }
+            console.log(obj.require, { require: 1 });
+            export {}
+        "#, // Input codes,
+        r#"
+            function load(require) {
+                return require('./a.js');
+            }
+            {
+                const require = (id) => id;
+                console.log(require('./b.js'));
+            }
+            console.log(obj.require, { require: 1 });
+            export {}
+        "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Default::default(),
+        |_| transform(serde_json::from_str(r#"{"target":"esm"}"#).unwrap()),
+        fn_shims_esm_require_shorthand,
+        r#"
+            const context = { require, __dirname };
+            console.log(require.resolve('./a.js'), require.cache);
+            export {}
+        "#, // Input codes,
+        r#"
+            import { createRequire as _createRequire } from "node:module";
+            const __require = _createRequire(import.meta.url);
+            const context = { require: __require, __dirname: import.meta.dirname };
+            console.log(__require.resolve('./a.js'), __require.cache);
+            export {}
+        "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Default::default(),
+        |_| transform(serde_json::from_str(r#"{"target":"cjs"}"#).unwrap()),
+        fn_shims_cjs_shorthand,
+        r#"
+            const { dirname } = import.meta;
+            console.log({ dirname });
+        "#, // Input codes,
+        r#"
+            console.log({ dirname: __dirname });
+        "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Default::default(),
+        |_| transform(serde_json::from_str(r#"{"target":"esm","interop":true}"#).unwrap()),
+        fn_shims_esm_interop_default,
+        r#"
+            import path from "node:path";
+            const { join } = require("./utils.cjs");
+            function resolve(p) {
+                return join(path.sep, p);
+            }
+            module.exports = { resolve };
+        "#, // Input codes,
+        r#"
+            import { createRequire as _createRequire } from "node:module";
+            const __require = _createRequire(import.meta.url);
+            import path from "node:path";
+            const { join } = __require("./utils.cjs");
+            function resolve(p) {
+                return join(path.sep, p);
+            }
+            export default { resolve };
+        "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Default::default(),
+        |_| transform(serde_json::from_str(r#"{"target":"esm","interop":true}"#).unwrap()),
+        fn_shims_esm_interop_named,
+        r#"
+            Object.defineProperty(exports, "__esModule", { value: true });
+            exports.foo = 1;
+            module.exports.bar = function () {
+                return exports.foo;
+            };
+            exports.default = "d";
+            function reset() {
+                exports.foo = 0;
+                exports.baz = true;
+            }
+            export {}
+        "#, // Input codes,
+        r#"
+            var baz;
+            var foo = 1;
+            var bar = function () {
+                return foo;
+            };
+            var _default = "d";
+            function reset() {
+                foo = 0;
+                baz = true;
+            }
+            export {}
+            export { foo as foo, bar as bar, _default as default, baz as baz };
+        "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Default::default(),
+        |_| transform(serde_json::from_str(r#"{"target":"esm","interop":true}"#).unwrap()),
+        fn_shims_esm_interop_shadowing,
+        r#"
+            function wrap(module, exports) {
+                module.exports = exports.value;
+            }
+            export {}
+        "#, // Input codes,
+        r#"
+            function wrap(module, exports) {
+                module.exports = exports.value;
+            }
+            export {}
+        "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Default::default(),
+        |_| transform(serde_json::from_str(r#"{"target":"cjs"}"#).unwrap()),
+        fn_shims_cjs_resolve,
+        r#"
+            console.log(import.meta.resolve("./a.js"));
+            const { resolve } = import.meta;
+            console.log(resolve("./b.js"));
+        "#, // Input codes,
+        r#"
+            console.log(require("url").pathToFileURL(require.resolve("./a.js")).href);
+            console.log(((specifier)=>require("url").pathToFileURL(require.resolve(specifier)).href)("./b.js"));
+        "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Default::default(),
+        |_| transform(serde_json::from_str(r#"{"target":"cjs","topLevelAwait":true}"#).unwrap()),
+        fn_shims_cjs_top_level_await,
+        r#"
+            "use strict";
+            const fs = require("fs");
+            const config = await load();
+            async function load() {
+                return await fs.promises.readFile("config.json", "utf8");
+            }
+            exports.config = config;
+        "#, // Input codes,
+        r#"
+            "use strict";
+            (async () => {
+                const fs = require("fs");
+                const config = await load();
+                async function load() {
+                    return await fs.promises.readFile("config.json", "utf8");
+                }
+                exports.config = config;
+            })();
+        "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Default::default(),
+        |_| transform(serde_json::from_str(r#"{"target":"cjs"}"#).unwrap()),
+        fn_shims_cjs_no_top_level_await,
+        r#"
+            async function load() {
+                for await (const chunk of stream) {}
+                return await fetch();
+            }
+            const run = async () => await load();
+        "#, // Input codes,
+        r#"
+            async function load() {
+                for await (const chunk of stream) {}
+                return await fetch();
+            }
+            const run = async () => await load();
+        "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Default::default(),
+        |_| transform(serde_json::from_str(r#"{"target":"cjs","cjsInterop":true}"#).unwrap()),
+        fn_shims_cjs_interop_default,
+        r#"
+            export default function add(a, b) {
+                return a + b;
+            }
+        "#, // Input codes,
+        r#"
+            export default function add(a, b) {
+                return a + b;
+            }
+            module.exports = exports.default;
+        "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Default::default(),
+        |_| transform(serde_json::from_str(r#"{"target":"cjs","cjsInterop":true}"#).unwrap()),
+        fn_shims_cjs_interop_named,
+        r#"
+            export const version = "1.0.0";
+            export default { parse() {} };
+        "#, // Input codes,
+        r#"
+            export const version = "1.0.0";
+            export default { parse() {} };
+            module.exports = Object.assign(exports.default, exports);
+        "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Default::default(),
+        |_| transform(serde_json::from_str(r#"{"target":"cjs","cjsInterop":true}"#).unwrap()),
+        fn_shims_cjs_interop_unsafe,
+        r#"
+            export const name = "add";
+            export default function add(a, b) {
+                return a + b;
+            }
+        "#, // Input codes,
+        r#"
+            export const name = "add";
+            export default function add(a, b) {
+                return a + b;
+            }
+        "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Default::default(),
+        |_| transform(serde_json::from_str(r#"{"target":"cjs","cjsInterop":true}"#).unwrap()),
+        fn_shims_cjs_interop_no_default,
+        r#"
+            export const a = 1;
+            export * from "./b";
+        "#, // Input codes,
+        r#"
+            export const a = 1;
+            export * from "./b";
+        "# // Output codes after transformed with plugin
+    );
+
+    #[test]
+    #[should_panic(expected = "`require` of a relative module can not be converted to ESM")]
+    fn test_esm_interop_relative_require() {
+        use swc_core::ecma::transforms::testing::Tester;
+
+        Tester::run(|tester| {
+            let module = tester.parse_module(
+                "input.js",
+                "const utils = require('./utils');\nmodule.exports = utils;",
+            )?;
+            Program::Module(module).mutate(transform(
+                serde_json::from_str(r#"{"target":"esm","interop":true}"#).unwrap(),
+            ));
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_cjs_interop_warnings() {
+        use swc_core::ecma::transforms::testing::Tester;
+
+        let stats = Arc::new(Mutex::new(ShimsUsage::default()));
+        Tester::run(|tester| {
+            let module =
+                tester.parse_module("input.js", "export const a = 1;\nexport default 'a';")?;
+            Program::Module(module).mutate(cjs_interop(Some(stats.clone())));
+            Ok(())
+        });
+
+        let usage = stats.lock().unwrap();
+        assert!(!usage.contains("module.exports"));
+        assert_eq!(usage.warnings.len(), 1);
+    }
+
+    test_inline!(
+        Default::default(),
+        |_| {
+            transform(
+            serde_json::from_str(
+                r#"{"target":"esm","platform":"browser","polyfill":{"process":"process/browser","buffer":"buffer"}}"#
+            )
+            .unwrap()
+        )
+        },
+        fn_shims_browser_esm,
+        r#"
+            const root = typeof window !== "undefined" ? window : global;
+            const env = { global, mode: process.env.MODE };
+            function encode(Buffer) {
+                return Buffer.from("a");
+            }
+            console.log(Buffer.from("b"), __dirname, require);
+            export {}
+        "#, // Input codes,
+        r#"
+            import process from "process/browser";
+            import { Buffer as Buffer } from "buffer";
+            const root = typeof window !== "undefined" ? window : globalThis;
+            const env = { global: globalThis, mode: process.env.MODE };
+            function encode(Buffer) {
+                return Buffer.from("a");
+            }
+            console.log(Buffer.from("b"), __dirname, require);
+            export {}
+        "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Default::default(),
+        |_| transform(
+            serde_json::from_str(
+                r#"{"target":"cjs","platform":"browser","polyfill":{"buffer":"buffer"}}"#
+            )
+            .unwrap()
+        ),
+        fn_shims_browser_cjs,
+        r#"
+            console.log(global.foo, process.env.MODE, Buffer.alloc(1));
+        "#, // Input codes,
+        r#"
+            const Buffer = require("buffer").Buffer;
+            console.log(globalThis.foo, process.env.MODE, Buffer.alloc(1));
+        "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Syntax::Typescript(Default::default()),
+        |_| top_level_await(true),
+        fn_top_level_await_type_export,
+        r#"
+            import { load } from "./load";
+            export type Config = { name: string };
+            export interface Options {}
+            const config: Config = await load();
+        "#, // Input codes,
+        r#"
+            import { load } from "./load";
+            export type Config = { name: string };
+            export interface Options {}
+            (async () => {
+                const config: Config = await load();
+            })();
+        "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Default::default(),
+        |_| import_meta(None),
+        fn_import_meta_esm_exports,
+        r#"
+            export const url = import.meta.url;
+            const { dirname } = import.meta;
+            export default { dirname };
+        "#, // Input codes,
+        r#"
+            export const url = require("url").pathToFileURL(__filename).toString();
+            export default { dirname: __dirname };
+        "# // Output codes after transformed with plugin
+    );
 }
@@ -0,0 +1,156 @@
+use swc_core::common::util::take::Take;
+use swc_core::common::{DUMMY_SP, Span};
+use swc_core::ecma::ast::{
+    ArrowExpr, AwaitExpr, BlockStmt, BlockStmtOrExpr, CallExpr, Callee, Class, Decl, ExportDecl,
+    Expr, ExprStmt, ForOfStmt, Function, Lit, ModuleDecl, ModuleItem, ParenExpr, Stmt,
+};
+use swc_core::ecma::visit::{Visit, VisitWith, noop_visit_type};
+
+use crate::error;
+
+/// CJS: top-level await
+/// - 默认报错
+/// - `topLevelAwait: true` 时包裹为 `(async () => { ... })();`
+pub(crate) fn transform(items: &mut Vec<ModuleItem>, wrap: bool) {
+    let mut finder = FindTopLevelAwait::default();
+    items.visit_with(&mut finder);
+
+    if finder.spans.is_empty() {
+        return;
+    }
+
+    if !wrap {
+        for span in finder.spans {
+            error(
+                span,
+                "top-level await is not supported in CommonJS output, move it into an async \
+                 function or enable `topLevelAwait` to wrap the module in an async IIFE",
+            );
+        }
+        return;
+    }
+
+    // export 声明无法放入函数中 (在 cjs 模块转换前执行, 包裹后导出将变为异步赋值)
+    // 类型导出在 ts 转换时移除, 不影响
+    let export_decl = items.iter().find_map(|item| match item {
+        ModuleItem::ModuleDecl(decl) if !is_import_or_type_export(decl) => Some(decl),
+        _ => None,
+    });
+    if let Some(decl) = export_decl {
+        error(
+            module_decl_span(decl),
+            "can not wrap top-level await in an async IIFE when the module has ESM exports",
+        );
+        return;
+    }
+
+    // "use strict" 等指令及 import 保留在顶层
+    let mut hoisted = vec![];
+    let mut stmts = vec![];
+    for item in items.take() {
+        match item {
+            ModuleItem::Stmt(stmt) if stmts.is_empty() && is_directive(&stmt) => {
+                hoisted.push(ModuleItem::Stmt(stmt))
+            }
+            ModuleItem::Stmt(stmt) => stmts.push(stmt),
+            decl => hoisted.push(decl),
+        }
+    }
+
+    // (async () => { ... })();
+    let iife = CallExpr {
+        span: DUMMY_SP,
+        callee: Callee::Expr(Box::new(Expr::Paren(ParenExpr {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Arrow(ArrowExpr {
+                span: DUMMY_SP,
+                ctxt: Default::default(),
+                params: vec![],
+                body: Box::new(BlockStmtOrExpr::BlockStmt(BlockStmt {
+                    span: DUMMY_SP,
+                    ctxt: Default::default(),
+                    stmts,
+                })),
+                is_async: true,
+                is_generator: false,
+                type_params: None,
+                return_type: None,
+            })),
+        }))),
+        args: vec![],
+        type_args: None,
+        ctxt: Default::default(),
+    };
+
+    hoisted.push(ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(Expr::Call(iife)),
+    })));
+
+    *items = hoisted;
+}
+
+fn is_directive(stmt: &Stmt) -> bool {
+    matches!(stmt, Stmt::Expr(ExprStmt { expr, .. }) if matches!(&**expr, Expr::Lit(Lit::Str(_))))
+}
+
+// import, export type, export interface, export declare ...
+fn is_import_or_type_export(decl: &ModuleDecl) -> bool {
+    match decl {
+        ModuleDecl::Import(_) => true,
+        ModuleDecl::ExportDecl(ExportDecl { decl, .. }) => match decl {
+            Decl::TsInterface(_) | Decl::TsTypeAlias(_) => true,
+            Decl::TsEnum(n) => n.declare,
+            Decl::TsModule(n) => n.declare,
+            Decl::Var(n) => n.declare,
+            Decl::Fn(n) => n.declare,
+            Decl::Class(n) => n.declare,
+            _ => false,
+        },
+        ModuleDecl::ExportNamed(n) => n.type_only,
+        ModuleDecl::ExportAll(n) => n.type_only,
+        ModuleDecl::TsImportEquals(n) => !n.is_export,
+        ModuleDecl::TsNamespaceExport(_) => true,
+        _ => false,
+    }
+}
+
+fn module_decl_span(decl: &ModuleDecl) -> Span {
+    match decl {
+        ModuleDecl::ExportDecl(n) => n.span,
+        ModuleDecl::ExportNamed(n) => n.span,
+        ModuleDecl::ExportDefaultDecl(n) => n.span,
+        ModuleDecl::ExportDefaultExpr(n) => n.span,
+        ModuleDecl::ExportAll(n) => n.span,
+        _ => DUMMY_SP,
+    }
+}
+
+/// 不进入函数, await 只在顶层作用域中查找
+#[derive(Default)]
+struct FindTopLevelAwait {
+    spans: Vec<Span>,
+}
+
+impl Visit for FindTopLevelAwait {
+    noop_visit_type!();
+
+    fn visit_function(&mut self, _: &Function) {}
+
+    fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+
+    fn visit_class(&mut self, _: &Class) {}
+
+    fn visit_await_expr(&mut self, n: &AwaitExpr) {
+        self.spans.push(n.span);
+        n.visit_children_with(self);
+    }
+
+    // for await (const x of xs)
+    fn visit_for_of_stmt(&mut self, n: &ForOfStmt) {
+        if n.is_await {
+            self.spans.push(n.span);
+        }
+        n.visit_children_with(self);
+    }
+}
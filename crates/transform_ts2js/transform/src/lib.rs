@@ -1,6 +1,7 @@
 use serde::Deserialize;
 use swc_core::ecma::ast::{self, Pass};
-use swc_core::ecma::visit::{VisitMut, VisitMutWith, noop_visit_mut_type, visit_mut_pass};
+use swc_core::ecma::visit::{VisitMut, VisitMutWith, visit_mut_pass};
+use swc_transform_extensions::specifier::{Bindings, is_url_with_import_meta};
 
 #[derive(Clone, Debug, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -32,10 +33,16 @@ fn replace_ts_extension(src: &ast::Str, config: &Config) -> Option<ast::Str> {
 
 struct RewriteImportExtensions {
     config: Config,
+    bindings: Bindings,
 }
 
 impl VisitMut for RewriteImportExtensions {
-    noop_visit_mut_type!();
+    // 不使用 noop_visit_mut_type!(), 需要访问 .d.ts 中的 import('./foo').A
+
+    fn visit_mut_program(&mut self, n: &mut ast::Program) {
+        self.bindings = Bindings::new(n);
+        n.visit_mut_children_with(self);
+    }
 
     // import './foo.ts'
     fn visit_mut_import_decl(&mut self, n: &mut ast::ImportDecl) {
@@ -67,10 +74,11 @@ impl VisitMut for RewriteImportExtensions {
     }
 
     // import('./foo.ts')
+    // require('./foo.ts'), require.resolve('./foo.ts'), import.meta.resolve('./foo.ts')
     fn visit_mut_call_expr(&mut self, n: &mut ast::CallExpr) {
         n.visit_mut_children_with(self);
 
-        if n.callee.is_import() && !n.args.is_empty() {
+        if self.bindings.is_specifier_call(&n.callee) && !n.args.is_empty() {
             if let Some(value) = n.args.first_mut() {
                 if let ast::Expr::Lit(ast::Lit::Str(src)) = value.expr.as_ref() {
                     if let Some(replaced) = replace_ts_extension(src, &self.config) {
@@ -80,15 +88,40 @@ impl VisitMut for RewriteImportExtensions {
             }
         }
     }
+
+    // .d.ts: type A = import('./foo.ts').A
+    fn visit_mut_ts_import_type(&mut self, n: &mut ast::TsImportType) {
+        n.visit_mut_children_with(self);
+
+        if let Some(replaced) = replace_ts_extension(&n.arg, &self.config) {
+            n.arg = replaced;
+        }
+    }
+
+    // new URL('./foo.ts', import.meta.url)
+    fn visit_mut_new_expr(&mut self, n: &mut ast::NewExpr) {
+        n.visit_mut_children_with(self);
+
+        if is_url_with_import_meta(n) {
+            if let Some(value) = n.args.as_mut().and_then(|args| args.first_mut()) {
+                if let ast::Expr::Lit(ast::Lit::Str(src)) = value.expr.as_ref() {
+                    if let Some(replaced) = replace_ts_extension(src, &self.config) {
+                        value.expr = Box::new(ast::Expr::Lit(ast::Lit::Str(replaced)));
+                    }
+                }
+            }
+        }
+    }
 }
 
 pub fn transform(config: Config) -> impl Pass {
-    visit_mut_pass(RewriteImportExtensions { config })
+    visit_mut_pass(RewriteImportExtensions { config, bindings: Default::default() })
 }
 
 #[cfg(test)]
 mod tests {
 
+    use swc_core::ecma::parser::{Syntax, TsSyntax};
     use swc_core::ecma::transforms::testing::test_inline;
 
     use super::*;
@@ -232,4 +265,58 @@ mod tests {
             import("./foo.cjs")
         "# // Output codes after transformed with plugin
     );
+
+    test_inline!(
+        Default::default(),
+        |_| transform(serde_json::from_str(r#"{}"#).unwrap()),
+        fn_require,
+        r#"
+            const a = require("./foo.ts");
+            const b = require.resolve("./foo.ts");
+            const c = import.meta.resolve("./foo.ts");
+            const d = new URL("./foo.ts", import.meta.url);
+            const e = new URL("./foo.ts", "https://example.com");
+            const f = load("./foo.ts");
+        "#, // Input codes,
+        r#"
+            const a = require("./foo.js");
+            const b = require.resolve("./foo.js");
+            const c = import.meta.resolve("./foo.js");
+            const d = new URL("./foo.js", import.meta.url);
+            const e = new URL("./foo.ts", "https://example.com");
+            const f = load("./foo.ts");
+        "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Default::default(),
+        |_| transform(serde_json::from_str(r#"{}"#).unwrap()),
+        fn_require_local,
+        r#"
+            const require = (path) => path;
+            const a = require("./foo.ts");
+            const b = import("./foo.ts");
+        "#, // Input codes,
+        r#"
+            const require = (path) => path;
+            const a = require("./foo.ts");
+            const b = import("./foo.js");
+        "# // Output codes after transformed with plugin
+    );
+
+    test_inline!(
+        Syntax::Typescript(TsSyntax { dts: true, ..Default::default() }),
+        |_| transform(serde_json::from_str(r#"{}"#).unwrap()),
+        fn_dts_import_type,
+        r#"
+            import type { A } from "./foo.ts";
+            export declare const a: typeof import("./foo.ts");
+            export type B = import("./foo.ts").B;
+        "#, // Input codes,
+        r#"
+            import type { A } from "./foo.js";
+            export declare const a: typeof import("./foo.js");
+            export type B = import("./foo.js").B;
+        "# // Output codes after transformed with plugin
+    );
 }
@@ -188,7 +188,10 @@ export const bundlessFiles = async (
 	};
 
 	// 是否可以收到 @shined/lecp-binding 内部??
-	let res = bundlessFilesAsync(Buffer.from(JSON.stringify(bundlessOptions)));
+	let res = bundlessFilesAsync(
+		Buffer.from(JSON.stringify(bundlessOptions)),
+		options.hooks,
+	);
 
 	const files = await glob("**/*", {
 		cwd: srcDir,
@@ -216,6 +219,7 @@ export const bundlessFiles = async (
 					return bundlessFileAsync(
 						filePath,
 						Buffer.from(JSON.stringify(bundlessOptions)),
+						options.hooks,
 					);
 				}
 
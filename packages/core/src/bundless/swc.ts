@@ -57,6 +57,7 @@ interface GetOptions {
 	alias: FinalUserConfig["alias"];
 	define: FinalUserConfig["define"];
 	shims?: FinalUserConfig["shims"];
+	plugins?: FinalUserConfig["plugins"];
 	outJsExt: string;
 	resolveDir?: boolean;
 	mode: Format["mode"];
@@ -107,13 +108,12 @@ export const getSwcOptions = (
 		react,
 		// css,
 		minify,
+		plugins = [],
 		// shims,
 		// outJsExt,
 		// mode,
 	} = options;
 
-	const plugins: Array<[string, Record<string, any>]> = [];
-
 	const defaultSwcOptions: SwcOptions = {
 		env: {
 			mode: "entry",
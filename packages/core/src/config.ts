@@ -289,10 +289,8 @@ export function getFinalFormatOptions(
 		formatOptions,
 	);
 
+	// shims: true 时 legacy 由 targets.node 自动选择
 	options.shims ??= false;
-	if (options.shims === true) {
-		options.shims = { legacy: false };
-	}
 
 	if (options.css?.cssModules === true) {
 		options.css.cssModules = `${systemConfig.pkg.name.replace("@", "").replace("/", "__")}__[local]`;
@@ -35,6 +35,21 @@ export type DtsOptions = {
 
 export type FormatMode = "bundless" | "bundle";
 
+/**
+ * bundless 构建钩子, 在 Rust worker 线程中同步调用 (不支持 async)
+ * @description 执行顺序: transform -> resolveId -> swc -> postTransform -> renderChunk -> 写入产物, 抛出错误会中断构建
+ */
+export type BundlessHooks = {
+	/** 重写导入路径, 返回 null / undefined 保持不变 */
+	resolveId?: (specifier: string, importer: string) => string | null | undefined;
+	/** swc 转换前, 返回 false 跳过该文件 */
+	transform?: (code: string, id: string) => string | false | null | undefined;
+	/** swc 转换后, 修改代码不会更新 sourcemap (开启 sourcemap 时输出警告) */
+	postTransform?: (code: string, id: string) => string | null | undefined;
+	/** 写入产物前, fileName 为产物路径; 修改代码不会更新 sourcemap (开启 sourcemap 时输出警告) */
+	renderChunk?: (code: string, fileName: string) => string | null | undefined;
+};
+
 /** 兼容目标环境 */
 export type BuildTarget = Record<string, string | number>;
 
@@ -190,12 +205,26 @@ export interface UserConfig {
 	 * 是否开启 shims
 	 * @description
 	 * - esm 产物, 支持 __dirname, __filename, require
-	 * 	- legacy: false(默认), 支持降级到(node@20.11+), 使用 import.meta.{dirname, filename}
-	 * 	- legacy: true,       支持降级到(node@10.12+), 使用 fileURLToPath
-	 * - cjs 产物, 支持 import.meta.{url,dirname, filename}
+	 * 	- legacy: false, 支持降级到(node@20.11+), 使用 import.meta.{dirname, filename}
+	 * 	- legacy: true,  支持降级到(node@10.12+), 使用 fileURLToPath
+	 * 	- 未指定 legacy 时根据 targets.node 自动选择
+	 * - cjs 产物, 支持 import.meta.{url,dirname, filename, resolve}
+	 * 	- topLevelAwait: true, 将包含 top-level await 的模块包裹为 async IIFE, 否则报错 (包含导出的模块无法包裹)
+	 * 	- cjsInterop: true, 有默认导出时 module.exports = default (具名导出挂载到默认导出上), 无需 require('pkg').default
+	 * - 非 node targets (浏览器): 不注入 node shims, global -> globalThis
+	 * 	- polyfill: { process?: string; buffer?: string }, 仅在引用了 process / Buffer 的文件中注入对应包的 import
+	 * - interop: true, esm 产物中 module.exports / exports.xxx 转换为 export default / export { xxx }
 	 *
 	 */
-	shims?: boolean | { legacy?: boolean };
+	shims?:
+		| boolean
+		| {
+				legacy?: boolean;
+				interop?: boolean;
+				cjsInterop?: boolean;
+				topLevelAwait?: boolean;
+				polyfill?: { process?: string; buffer?: string };
+		  };
 
 	/**
 	 * 设置别名
@@ -210,6 +239,22 @@ export interface UserConfig {
 	 */
 	define?: Record<string, string>;
 
+	/**
+	 * 加载 .env 文件, 替换 `import.meta.env.X` / `process.env.X` (bundless)
+	 * @description
+	 * - 加载顺序 (后者覆盖前者): `.env`, `.env.local`, `.env.[mode]`, `.env.[mode].local`, 进程环境变量
+	 * - 只暴露以 prefix 开头的变量, lecp 内部变量 (`LECP_LOG`) 不会暴露
+	 * @default undefined
+	 */
+	env?: {
+		/** `.env.[mode]`, `import.meta.env.MODE` @default "production" */
+		mode?: string;
+		/** @default ["LECP_", "VITE_"] */
+		prefix?: string[];
+		/** env 文件所在目录, 相对项目根目录 @default 项目根目录 */
+		dir?: string;
+	};
+
 	/**
 	 * 是否生成声明文件
 	 * @default true (mode:"bundless" + builder: "ts")
@@ -293,6 +338,32 @@ export interface UserConfig {
 	 * swc 编译选项
 	 */
 	swcOptions?: SwcOptions;
+
+	/**
+	 * swc Wasm 插件, 等同于 `jsc.experimental.plugins`
+	 * @description
+	 * - name 为 npm 包名 (从 node_modules 解析) 或 .wasm 文件路径 (相对 cwd)
+	 * - 编译缓存默认位于 `node_modules/.cache/swc`, 可通过 `swcOptions.jsc.experimental.cacheRoot` 修改
+	 * @example [["@swc/plugin-emotion", {}], ["./plugins/my-plugin.wasm", { foo: true }]]
+	 */
+	plugins?: Array<[string, Record<string, any>]>;
+
+	/**
+	 * bundless 构建钩子 (JS), 无需编写 swc 插件即可注入 banner、重写导入、跳过文件等
+	 */
+	hooks?: BundlessHooks;
+
+	/**
+	 * 产物头部内容, 如 license 注释、`"use client";`
+	 * @description bundless 模式下支持模板变量 `[name]` (源文件名, 不含后缀)、`[path]` (相对源码目录 `src` 的源文件路径); banner 位于 `#!` 之后
+	 * @example '/*! my-lib | [path] *\/'
+	 */
+	banner?: string;
+
+	/**
+	 * 产物尾部内容, 支持同 banner 的模板变量
+	 */
+	footer?: string;
 }
 
 export type UserConfigFn = () => UserConfig | Promise<UserConfig>;